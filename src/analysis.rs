@@ -0,0 +1,82 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::input::{Content};
+use crate::config::{InitialDepth};
+
+pub const DEFAULT_DEPTH: usize = 1;
+
+// Items whose value names overlap the dominant signature by at least this much
+// are treated as repetitions of the same kind of item
+const SIMILARITY_THRESHOLD: f64 = 0.5;
+
+pub fn resolve_depth(initial_depth: &InitialDepth, content: &Content) -> usize {
+    match initial_depth {
+        InitialDepth::Fixed(depth) => *depth,
+        InitialDepth::Auto => suggest_depth(content).unwrap_or(DEFAULT_DEPTH),
+    }
+}
+
+pub fn suggest_depth(content: &Content) -> Option<usize> {
+    log::trace!("In suggest_depth");
+
+    let mut best: Option<(usize, f64)> = None;
+
+    for depth in 0..=content.inner_depth() + 1 {
+        let mut items = Vec::new();
+        content.go_down_depth(depth, &mut items);
+
+        let score = homogeneity_score(&items);
+        log::debug!("depth: {}, items: {}, score: {}", depth, items.len(), score);
+
+        if score > best.map(|(_, best_score)| best_score).unwrap_or(0.0) {
+            best = Some((depth, score));
+        }
+    }
+
+    best.map(|(depth, _)| depth)
+}
+
+fn homogeneity_score(items: &Vec<Content>) -> f64 {
+    let signatures: Vec<BTreeSet<String>> = items
+        .iter()
+        .map(|item| item.values.iter().map(|value| value.name.clone()).collect())
+        .filter(|signature: &BTreeSet<String>| !signature.is_empty())
+        .collect();
+
+    if signatures.len() < 2 {
+        return 0.0;
+    }
+
+    let mut counts: HashMap<&BTreeSet<String>, usize> = HashMap::new();
+    for signature in &signatures {
+        *counts.entry(signature).or_insert(0) += 1;
+    }
+
+    let dominant = counts
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+        .map(|(signature, _)| *signature)
+        .unwrap();
+
+    let similar = signatures
+        .iter()
+        .filter(|signature| similarity(signature, dominant) >= SIMILARITY_THRESHOLD)
+        .count();
+
+    if similar < 2 {
+        return 0.0;
+    }
+
+    (similar * similar) as f64 / items.len() as f64
+}
+
+fn similarity(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let intersection = a.intersection(b).count();
+    let union = a.union(b).count();
+
+    if union == 0 {
+        0.0
+    } else {
+        intersection as f64 / union as f64
+    }
+}
@@ -1,5 +1,5 @@
 use crossterm::{
-    event::{self, Event, KeyCode::Char, KeyCode},
+    event::{self, Event, KeyCode::Char, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -7,43 +7,136 @@ use ratatui::{prelude::*, widgets::*};
 use ratatui::{widgets::List as RList};
 use ratatui::{widgets::ListItem as RListItem};
 use textwrap;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io::{Write};
+use std::process::{Child};
+use std::sync::mpsc::{Receiver, TryRecvError};
 use std::str::FromStr;
 
-use crate::input::{Input, Content};
-use crate::session::{Session};
+use crate::input::{Input, Content, ContentValue, ContentFilter, LineOptions, RenderedLines, ValuePath, DEFAULT_LINE_WIDTH};
+use crate::session::{Session, SelectedValue};
 use crate::history::{History};
+use crate::config::{Config, ColorPalette};
+use crate::keymap::{Action, Key, Keymap};
+use crate::options::{ClipboardOutput, Loader, RenderOptions};
+use crate::analysis::{DEFAULT_DEPTH, resolve_depth};
+use crate::table::{TableSort, table_keys};
+use crate::clipboard;
+use crate::opener;
+use crate::command::{Command, PipeSource, parse_command};
+use crate::shell::{CommandOutput, spawn_with_input};
+use crate::export::{ExportScope, export};
 
-const DEFAULT_DEPTH: usize = 1;
+const CARD_MIN_WIDTH: u16 = 40;
+const CARD_HEIGHT: u16 = 7;
 
-//const DEFAULT_PRIMARY_COLOR_HEX: &str = "#00FF00"; // green
-//const DEFAULT_SECONDARY_COLOR_HEX: &str = "#FFFFFF"; // white
-//const DEFAULT_BACKGROUND_COLOR_HEX: &str = "#000011"; // black
+const HIGHLIGHT_COLOR_HEX: &str = "#00FF00";
+const MARK_COLOR_HEX: &str = "#FFFF66";
 
-const DEFAULT_PRIMARY_COLOR_HEX: &str = "#FF6600";
-const DEFAULT_SECONDARY_COLOR_HEX: &str = "#828282";
-const DEFAULT_BACKGROUND_COLOR_HEX: &str = "#F6F6EF";
+// How a session ended, returned to whoever drives the event loop
+#[derive(Clone, Debug)]
+pub enum AppOutcome {
+    Selected(Session),
+    Back(String),
+    Quit,
+}
 
-pub struct ColorPalette {
-    pub primary_hex: String,
-    pub secondary_hex: String,
-    pub background_hex: String,
+#[derive(Clone, Debug, PartialEq)]
+pub enum ViewMode {
+    List,
+    Thread,
+    Cards,
 }
 
 pub struct App {
     pub should_quit: bool,
-    pub should_display_primary_content: bool,
+    pub content_filter: ContentFilter,
     pub session: Session,
     pub display_items: StatefulList<ComplexObject>,
     pub color_palette: ColorPalette,
+    pub view_mode: ViewMode,
+    keymap: Keymap,
+    loader: Option<Loader>,
+    back_stack: Vec<Input>,
+    outcome: Option<AppOutcome>,
+    config: Config,
     current_depth: usize,
     input: Option<Input>,
     history: Option<History>,
-    current_value_index: usize,
-    current_value: Option<String>,
+    value_cursor: ValueCursor,
+    zoom_stack: Vec<ZoomFrame>,
+    collapsed: HashSet<String>,
+    folded_lists: HashSet<String>,
+    table_sorts: HashMap<String, TableSort>,
+    card_columns: usize,
+    card_row_offset: usize,
+    marks: Vec<Mark>,
+    visual_anchor: Option<String>,
+    search: Option<String>,
+    prompt: Option<Prompt>,
+    pending_yank: bool,
+    status: Option<String>,
+    launched: Vec<(String, Child)>,
+    output_pane: Option<OutputPane>,
+    running: Option<RunningCommand>,
+    side_effects: bool,
+    clipboard_output: Option<ClipboardOutput>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ComplexObject {
+    pub content: Content,
+    pub thread_level: usize,
+    pub reply_count: usize,
+}
+
+// Position of the value cursor within the value grid of the item it was
+// placed on. It no longer applies once a different item is selected
+#[derive(Clone, Debug, Default)]
+struct ValueCursor {
+    item_id: Option<String>,
+    row: usize,
+    column: usize,
 }
 
-type ComplexObject = Content;
+// A marked item returns its default link, a marked value returns itself.
+// The value is resolved when marking so marks survive depth and filter changes
+#[derive(Clone, Debug)]
+struct Mark {
+    item_id: String,
+    path: Option<ValuePath>,
+    value: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PromptKind {
+    Search,
+    Command,
+}
+
+#[derive(Clone, Debug)]
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub text: String,
+}
+
+struct OutputPane {
+    title: String,
+    output: CommandOutput,
+    scroll: u16,
+}
+
+// A piped command still working on its own thread
+struct RunningCommand {
+    command: String,
+    result: Receiver<Result<CommandOutput, String>>,
+}
+
+struct ZoomFrame {
+    content: Content,
+    depth: usize,
+    selected: Option<String>,
+}
 
 pub struct StatefulList<T> {
     state: ListState,
@@ -52,53 +145,456 @@ pub struct StatefulList<T> {
 }
 
 impl App {
-    pub fn new() -> App {
+    pub(crate) fn new() -> App {
         App {
             should_quit: false,
-            should_display_primary_content: true,
+            content_filter: ContentFilter::Everything,
             display_items: StatefulList::<ComplexObject>::with_items(Vec::new()),
             session: Session {
                 depth: DEFAULT_DEPTH,
                 value: None,
+                values: Vec::new(),
             },
             current_depth: DEFAULT_DEPTH,
-            color_palette: ColorPalette {
-                primary_hex: DEFAULT_PRIMARY_COLOR_HEX.to_string(),
-                secondary_hex: DEFAULT_SECONDARY_COLOR_HEX.to_string(),
-                background_hex: DEFAULT_BACKGROUND_COLOR_HEX.to_string(),
-            },
+            view_mode: ViewMode::List,
+            config: Config::default(),
+            color_palette: ColorPalette::default(),
+            keymap: Keymap::default(),
+            loader: None,
+            back_stack: Vec::new(),
+            outcome: None,
             input: None,
             history: None,
-            current_value_index: 0,
-            current_value: None,
+            value_cursor: ValueCursor::default(),
+            zoom_stack: Vec::new(),
+            collapsed: HashSet::new(),
+            folded_lists: HashSet::new(),
+            table_sorts: HashMap::new(),
+            card_columns: 1,
+            card_row_offset: 0,
+            marks: Vec::new(),
+            visual_anchor: None,
+            search: None,
+            prompt: None,
+            pending_yank: false,
+            status: None,
+            launched: Vec::new(),
+            output_pane: None,
+            running: None,
+            side_effects: true,
+            clipboard_output: None,
+        }
+    }
+
+    // Builds an app ready to be drawn as a widget and fed events, for hosts
+    // that run their own terminal and event loop
+    pub fn with_input(input: &Input, options: RenderOptions) -> App {
+        let mut app = App::new();
+        app.load_config(&options.config);
+        app.load_keymap(options.keymap);
+        app.load_loader(options.loader);
+        app.load_input(input);
+        app.load_history(&options.history);
+        app.side_effects = options.side_effects;
+        app.clipboard_output = options.clipboard_output;
+
+        if let Some(id) = &options.selection {
+            app.select_item(id);
         }
+
+        app
     }
 
     pub fn quit(&mut self) {
+        self.finish(AppOutcome::Quit);
+    }
+
+    fn finish(&mut self, outcome: AppOutcome) {
         self.should_quit = true;
+        self.outcome = Some(outcome);
+    }
+
+    fn finish_with_selection(&mut self) {
+        let session = self.get_session();
+        self.finish(AppOutcome::Selected(session));
+    }
+
+    pub fn draw<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> std::io::Result<()> {
+        terminal.draw(|f| f.render_widget(self, f.size()))?;
+        Ok(())
+    }
+
+    pub fn handle_event(&mut self, event: Event) -> Option<AppOutcome> {
+        if let Event::Key(key) = event {
+            self.handle_key(key);
+        }
+
+        self.poll_launched();
+
+        self.outcome.take()
     }
     
-    pub fn toggle_primary_content(&mut self) {
-        self.should_display_primary_content = !self.should_display_primary_content;
+    pub fn cycle_content_filter(&mut self) {
+        let selected = self.get_current_content();
+
+        self.content_filter = self.content_filter.next();
+        self.init_display_items();
+        self.select_by_id(selected.map(|content| content.id));
     }
 
     pub fn deeper(&mut self) {
+        let selected = self.get_current_content();
+
         self.current_depth = self.current_depth + 1;
         self.init_display_items();
+
+        if let Some(content) = selected {
+            let index = self.display_items.items
+                .iter()
+                .position(|item| item.content.id == content.id)
+                .or(
+                    self.display_items.items
+                        .iter()
+                        .position(|item| content.contains_id(&item.content.id))
+                );
+
+            self.display_items.state.select(index);
+        }
     }
 
     pub fn higher(&mut self) {
         if self.current_depth > 0 {
+            let selected = self.get_current_content();
+
             self.current_depth = self.current_depth - 1;
             self.init_display_items();
+
+            if let Some(content) = selected {
+                let index = self.display_items.items
+                    .iter()
+                    .position(|item| item.content.id == content.id || item.content.contains_id(&content.id));
+
+                self.display_items.state.select(index);
+            }
         }
     }
     
+    pub fn zoom_in(&mut self) {
+        if let Some(content) = self.get_current_content() {
+            if !content.has_nested_content() {
+                log::debug!("Selected content has nothing to zoom into");
+                return;
+            }
+
+            self.zoom_stack.push(ZoomFrame {
+                content: content,
+                depth: self.current_depth,
+                selected: self.get_selected_id(),
+            });
+
+            self.current_depth = 1;
+            self.init_display_items();
+            self.display_items.start();
+        }
+    }
+
+    pub fn zoom_out(&mut self) {
+        if let Some(frame) = self.zoom_stack.pop() {
+            self.current_depth = frame.depth;
+            self.init_display_items();
+            self.select_by_id(frame.selected);
+        }
+    }
+
+    pub fn toggle_thread_mode(&mut self) {
+        let selected = self.get_current_content();
+
+        self.view_mode = match self.view_mode {
+            ViewMode::Thread => ViewMode::List,
+            _ => ViewMode::Thread,
+        };
+
+        self.init_display_items();
+        self.select_by_id(selected.map(|content| content.id));
+    }
+
+    pub fn toggle_card_mode(&mut self) {
+        let selected = self.get_current_content();
+
+        self.view_mode = match self.view_mode {
+            ViewMode::Cards => ViewMode::List,
+            _ => ViewMode::Cards,
+        };
+
+        self.init_display_items();
+        self.select_by_id(selected.map(|content| content.id));
+    }
+
+    pub fn move_up(&mut self) {
+        match self.view_mode {
+            ViewMode::Cards => self.move_card_selection(-(self.card_columns as isize)),
+            _ => self.display_items.previous(),
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        match self.view_mode {
+            ViewMode::Cards => self.move_card_selection(self.card_columns as isize),
+            _ => self.display_items.next(),
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        match self.view_mode {
+            ViewMode::Cards => self.move_card_selection(-1),
+            _ => self.previous_value(),
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        match self.view_mode {
+            ViewMode::Cards => self.move_card_selection(1),
+            _ => self.next_value(),
+        }
+    }
+
+    pub fn toggle_collapse(&mut self) {
+        if self.view_mode != ViewMode::Thread {
+            return;
+        }
+
+        if let Some(content) = self.get_current_content() {
+            if content.children.is_empty() {
+                return;
+            }
+
+            if !self.collapsed.remove(&content.id) {
+                self.collapsed.insert(content.id.clone());
+            }
+
+            self.init_display_items();
+            self.select_by_id(Some(content.id));
+        }
+    }
+
+    pub fn toggle_fold_lists(&mut self) {
+        if let Some(content) = self.get_current_content() {
+            let mut list_keys = Vec::new();
+            content.list_keys(&mut list_keys);
+
+            if list_keys.iter().all(|key| self.folded_lists.contains(key)) {
+                for key in list_keys {
+                    self.folded_lists.remove(&key);
+                }
+            } else {
+                self.folded_lists.extend(list_keys);
+            }
+        }
+    }
+
+    pub fn cycle_table_sort(&mut self) {
+        if let Some(content) = self.get_current_content() {
+            let mut tables = Vec::new();
+            table_keys(&content, &mut tables);
+
+            for (key, column_count) in tables {
+                match self.table_sorts.get(&key).map(|sort| sort.column + 1) {
+                    Some(column) if column >= column_count => {
+                        self.table_sorts.remove(&key);
+                    }
+                    column => {
+                        self.table_sorts.insert(key, TableSort {
+                            column: column.unwrap_or(0),
+                            descending: false,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn reverse_table_sort(&mut self) {
+        if let Some(content) = self.get_current_content() {
+            let mut tables = Vec::new();
+            table_keys(&content, &mut tables);
+
+            for (key, _) in tables {
+                if let Some(sort) = self.table_sorts.get_mut(&key) {
+                    sort.descending = !sort.descending;
+                }
+            }
+        }
+    }
+
+    pub fn jump_to_parent(&mut self) {
+        if let Some(i) = self.display_items.state.selected() {
+            let level = self.display_items.items[i].thread_level;
+
+            if level > 0 {
+                let parent = self.display_items.items[..i]
+                    .iter()
+                    .rposition(|item| item.thread_level == level - 1);
+
+                if parent.is_some() {
+                    self.display_items.state.select(parent);
+                }
+            }
+        }
+    }
+
+    pub fn jump_to_next_sibling(&mut self) {
+        if let Some(i) = self.display_items.state.selected() {
+            let level = self.display_items.items[i].thread_level;
+
+            let next = self.display_items.items
+                .iter()
+                .enumerate()
+                .skip(i + 1)
+                .find(|(_, item)| item.thread_level <= level);
+
+            if let Some((j, item)) = next {
+                if item.thread_level == level {
+                    self.display_items.state.select(Some(j));
+                }
+            }
+        }
+    }
+
+    pub fn jump_to_next_thread(&mut self) {
+        if let Some(i) = self.display_items.state.selected() {
+            let next = self.display_items.items
+                .iter()
+                .enumerate()
+                .skip(i + 1)
+                .find(|(_, item)| item.thread_level == 0)
+                .map(|(j, _)| j);
+
+            if next.is_some() {
+                self.display_items.state.select(next);
+            }
+        }
+    }
+
     pub fn get_session(&self) -> Session {
-        self.session.clone()
+        let mut session = self.session.clone();
+        session.depth = self.current_depth;
+        session
+    }
+
+    pub fn load_config(&mut self, config: &Config) {
+        self.config = config.clone();
+
+        if config.theme.is_valid() {
+            self.color_palette = config.theme.clone();
+        } else {
+            log::error!("Invalid theme, using the default: {:?}", config.theme);
+        }
+    }
+
+    pub fn load_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    pub fn load_loader(&mut self, loader: Option<Loader>) {
+        self.loader = loader;
+    }
+
+    // Selects the item with this id, changing depth when it is not shown at the
+    // current one
+    pub fn select_item(&mut self, id: &str) {
+        let inner_depth = self.get_root().inner_depth();
+
+        // Lists and children of the deepest inner content sit one level below it
+        let depth = std::iter::once(self.current_depth).chain(0..=inner_depth + 1).find(|depth| {
+            let mut results = Vec::new();
+            self.get_root().go_down_depth(*depth, &mut results);
+            results.iter().any(|content| content.id == id)
+        });
+
+        if let Some(depth) = depth {
+            if depth != self.current_depth {
+                self.current_depth = depth;
+                self.init_display_items();
+            }
+
+            self.select_by_id(Some(id.to_string()));
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+
+        // Raw mode turns off the terminal's own handling of Ctrl-C
+        if key.code == Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.exit_without_value();
+            return;
+        }
+
+        if self.has_output_pane() {
+            self.handle_output_pane_key(key.code);
+        } else if self.is_prompting() {
+            self.handle_prompt_key(key.code);
+        } else if self.is_yanking() {
+            self.handle_yank_key(key.code);
+        } else {
+            self.clear_status();
+
+            if let Some(action) = self.keymap.action_for(&Key::from(key)) {
+                self.perform(action);
+            }
+        }
+    }
+
+    pub fn perform(&mut self, action: Action) {
+        log::debug!("action: {:?}", action);
+
+        match action {
+            Action::Quit => self.exit_without_value(),
+            Action::First => self.display_items.start(),
+            Action::Last => self.display_items.end(),
+            Action::Down => self.move_down(),
+            Action::Up => self.move_up(),
+            Action::Left => self.move_left(),
+            Action::Right => self.move_right(),
+            Action::NextValueLine => self.next_value_line(),
+            Action::PreviousValueLine => self.previous_value_line(),
+            Action::CycleFilter => self.cycle_content_filter(),
+            Action::Higher => self.higher(),
+            Action::Deeper => self.deeper(),
+            Action::ZoomIn => self.zoom_in(),
+            Action::ZoomOut => self.zoom_out(),
+            Action::ToggleThreadView => self.toggle_thread_mode(),
+            Action::ToggleCardView => self.toggle_card_mode(),
+            Action::ToggleCollapse => self.toggle_collapse(),
+            Action::ToggleFoldLists => self.toggle_fold_lists(),
+            Action::CycleTableSort => self.cycle_table_sort(),
+            Action::ReverseTableSort => self.reverse_table_sort(),
+            Action::JumpToParent => self.jump_to_parent(),
+            Action::NextSibling => self.jump_to_next_sibling(),
+            Action::NextThread => self.jump_to_next_thread(),
+            Action::ExitWithValue => self.exit_with_value(),
+            Action::ToggleMark => self.toggle_mark(),
+            Action::Yank => self.start_yank(),
+            Action::OpenLink => self.open_link(),
+            Action::CommandLine => self.start_command(""),
+            Action::ShellCommand => self.start_command("!"),
+            Action::ToggleVisual => self.toggle_visual_mode(),
+            Action::Search => self.start_search(),
+            Action::NextMatch => self.next_match(),
+            Action::PreviousMatch => self.previous_match(),
+            Action::MarkAllMatches => self.mark_all_matches(),
+            Action::Cancel => self.cancel(),
+            Action::Confirm => self.confirm(),
+            Action::Back => self.try_navigate_back(),
+        }
     }
 
     pub fn load_input(&mut self, input: &Input) {
+        self.current_depth = resolve_depth(&self.config.initial_depth, &input.content);
+        log::debug!("initial depth: {}", self.current_depth);
+
         self.input = Some(input.clone());
         self.init_display_items();
     }
@@ -108,17 +604,377 @@ impl App {
     }
 
     pub fn first_value(&mut self) {
-        self.current_value_index = 0;
+        self.value_cursor = ValueCursor {
+            item_id: self.get_selected_id(),
+            row: 0,
+            column: 0,
+        };
     }
 
     pub fn next_value(&mut self) {
-        self.current_value_index += 1;
+        self.move_value_cursor(0, 1);
+    }
+
+    pub fn previous_value(&mut self) {
+        self.move_value_cursor(0, -1);
+    }
+
+    pub fn next_value_line(&mut self) {
+        self.move_value_cursor(1, 0);
+    }
+
+    pub fn previous_value_line(&mut self) {
+        self.move_value_cursor(-1, 0);
+    }
+
+    pub fn get_current_value(&self) -> Option<ContentValue> {
+        let grid = self.get_value_grid();
+        let (row, column) = self.get_value_cursor_position(&grid)?;
+
+        Some(grid[row][column].clone())
+    }
+
+    pub fn toggle_mark(&mut self) {
+        let item_id = match self.get_selected_id() {
+            Some(item_id) => item_id,
+            None => return,
+        };
+
+        let path = if self.value_cursor.item_id.as_ref() == Some(&item_id) {
+            self.get_highlighted_path()
+        } else {
+            None
+        };
+
+        let existing = match &path {
+            Some(_) => self.marks.iter().position(|mark| mark.path == path),
+            None => self.marks.iter().position(|mark| mark.item_id == item_id && mark.path.is_none()),
+        };
+
+        match existing {
+            Some(index) => {
+                self.marks.remove(index);
+            }
+            None => {
+                let value = match path {
+                    Some(_) => self.get_current_value().map(|value| value.value.trim().to_string()),
+                    None => self.get_item_mark_value(),
+                };
+
+                if let Some(value) = value {
+                    self.marks.push(Mark {
+                        item_id: item_id,
+                        path: path,
+                        value: value,
+                    });
+                }
+            }
+        }
+    }
+
+    pub fn toggle_visual_mode(&mut self) {
+        match self.visual_anchor {
+            Some(_) => self.mark_visual_range(),
+            None => {
+                if self.display_items.state.selected().is_none() {
+                    self.display_items.start();
+                }
+
+                self.visual_anchor = self.get_selected_id();
+            }
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        self.visual_anchor = None;
+        self.prompt = None;
+
+        // The command is left to finish on its own, only its output is dropped
+        if let Some(running) = self.running.take() {
+            self.status = Some(format!("Stopped waiting for: {}", running.command));
+        }
+    }
+
+    pub fn start_search(&mut self) {
+        self.prompt = Some(Prompt {
+            kind: PromptKind::Search,
+            text: String::new(),
+        });
+    }
+
+    pub fn start_command(&mut self, initial: &str) {
+        self.prompt = Some(Prompt {
+            kind: PromptKind::Command,
+            text: initial.to_string(),
+        });
+    }
+
+    pub fn is_prompting(&self) -> bool {
+        self.prompt.is_some()
+    }
+
+    pub fn handle_prompt_key(&mut self, code: KeyCode) {
+        if let Some(prompt) = &mut self.prompt {
+            match code {
+                KeyCode::Char(c) => prompt.text.push(c),
+                KeyCode::Backspace => {
+                    prompt.text.pop();
+                }
+                KeyCode::Esc => self.prompt = None,
+                KeyCode::Enter => {
+                    let prompt = self.prompt.take().unwrap();
+
+                    match prompt.kind {
+                        PromptKind::Search => {
+                            self.search = if prompt.text.is_empty() { None } else { Some(prompt.text) };
+                            self.next_match();
+                        }
+                        PromptKind::Command => {
+                            match parse_command(&prompt.text) {
+                                Ok(command) => self.run_command(command),
+                                Err(e) => self.status = Some(e),
+                            }
+                        }
+                    }
+                }
+                _ => {},
+            }
+        }
+    }
+
+    pub fn run_command(&mut self, command: Command) {
+        match command {
+            Command::Pipe { source, command } => {
+                let input = match source {
+                    PipeSource::Value => self.get_current_value().map(|value| value.value.trim().to_string()),
+                    PipeSource::Text => self.get_current_item_text(),
+                    PipeSource::Json => self.get_current_item_json(),
+                };
+
+                let input = match input {
+                    Some(input) => input,
+                    None => {
+                        self.status = Some("Nothing selected to pipe".to_string());
+                        return;
+                    }
+                };
+
+                if !self.side_effects {
+                    self.status = Some(format!("Would run: {}", command));
+                    return;
+                }
+
+                if let Some(running) = &self.running {
+                    self.status = Some(format!("Still running: {}", running.command));
+                    return;
+                }
+
+                self.running = Some(RunningCommand {
+                    result: spawn_with_input(&command, &input),
+                    command: command,
+                });
+            }
+            Command::Export { format, path, scope } => {
+                let contents: Vec<Content> = match scope {
+                    ExportScope::Document => vec![self.get_root()],
+                    ExportScope::Depth => self.display_items.items
+                        .iter()
+                        .filter(|item| item.thread_level == 0)
+                        .map(|item| item.content.clone())
+                        .collect(),
+                };
+
+                if !self.side_effects {
+                    self.status = Some(format!("Would export {} items to {}", contents.len(), path));
+                    return;
+                }
+
+                let exported = export(&contents, &format, &self.content_filter, &self.config.list_style);
+
+                match std::fs::write(&path, exported) {
+                    Ok(()) => self.status = Some(format!("Exported {} items to {}", contents.len(), path)),
+                    Err(e) => {
+                        log::error!("Failed to write {}: {}", path, e);
+                        self.status = Some(format!("Export failed: {}: {}", path, e));
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn has_output_pane(&self) -> bool {
+        self.output_pane.is_some()
+    }
+
+    pub fn handle_output_pane_key(&mut self, code: KeyCode) {
+        if let Some(pane) = &mut self.output_pane {
+            match code {
+                KeyCode::Char('j') | KeyCode::Down => pane.scroll = pane.scroll.saturating_add(1),
+                KeyCode::Char('k') | KeyCode::Up => pane.scroll = pane.scroll.saturating_sub(1),
+                KeyCode::Char('g') => pane.scroll = 0,
+                KeyCode::Char('G') => {
+                    let lines = pane.output.stdout.lines().count() + pane.output.stderr.lines().count();
+                    pane.scroll = u16::try_from(lines).unwrap_or(u16::MAX);
+                }
+                KeyCode::Char('q') | KeyCode::Esc => self.output_pane = None,
+                KeyCode::Char('r') => self.replace_input_with_output(),
+                _ => {},
+            }
+        }
+    }
+
+    pub fn next_match(&mut self) {
+        self.jump_to_match(true);
+    }
+
+    pub fn previous_match(&mut self) {
+        self.jump_to_match(false);
+    }
+
+    pub fn mark_all_matches(&mut self) {
+        let matches: Vec<usize> = self.get_matching_indices();
+
+        for index in matches {
+            self.mark_item(index);
+        }
+    }
+
+    pub fn start_yank(&mut self) {
+        self.pending_yank = true;
+        self.status = Some("yank: [y]/[v]alue, [t]ext, [j]son".to_string());
+    }
+
+    pub fn is_yanking(&self) -> bool {
+        self.pending_yank
+    }
+
+    pub fn handle_yank_key(&mut self, code: KeyCode) {
+        self.pending_yank = false;
+        self.status = None;
+
+        let (label, text) = match code {
+            KeyCode::Char('y') | KeyCode::Char('v') => {
+                ("value", self.get_current_value().map(|value| value.value.trim().to_string()))
+            }
+            KeyCode::Char('t') => ("item text", self.get_current_item_text()),
+            KeyCode::Char('j') => ("item JSON", self.get_current_item_json()),
+            _ => return,
+        };
+
+        match text {
+            Some(text) if !self.side_effects => {
+                self.status = Some(format!("Would copy {} ({} chars)", label, text.chars().count()));
+            }
+            Some(text) => {
+                let out = self.clipboard_output.as_mut().map(|out| out.as_mut() as &mut dyn Write);
+                let copied = clipboard::copy(&text, &self.config.clipboard_command, out);
+
+                match copied {
+                    Ok(method) => {
+                        self.status = Some(format!("Copied {} ({} chars) via {}", label, text.chars().count(), method));
+                    }
+                    Err(e) => {
+                        log::error!("Failed to copy: {}", e);
+                        self.status = Some(format!("Copy failed: {}", e));
+                    }
+                }
+            }
+            None => {
+                self.status = Some(format!("Nothing to copy as {}", label));
+            }
+        }
+    }
+
+    pub fn open_link(&mut self) {
+        let link = self.get_current_value()
+            .filter(|value| value.meta.is_url)
+            .or_else(|| self.get_current_content().and_then(|content| content.get_default_link()));
+
+        let url = match link {
+            Some(link) => link.value.trim().to_string(),
+            None => {
+                self.status = Some("No link to open".to_string());
+                return;
+            }
+        };
+
+        let command = opener::resolve_command(&url, &self.config.opener, &self.config.open_rules);
+
+        if !self.side_effects {
+            self.status = Some(format!("Would open {} with {}", url, command));
+            return;
+        }
+
+        match opener::spawn(&url, &command) {
+            Ok(child) => {
+                self.status = Some(format!("Opening {}", url));
+                self.launched.push((url, child));
+            }
+            Err(e) => {
+                log::error!("{}", e);
+                self.status = Some(format!("Open failed: {}", e));
+            }
+        }
+    }
+
+    // Reaps finished opener processes and reports the ones that failed, and
+    // shows the output of a piped command once it is done
+    pub fn poll_launched(&mut self) {
+        self.poll_running();
+
+        let mut failures: Vec<String> = Vec::new();
+
+        self.launched.retain_mut(|(url, child)| {
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    if !status.success() {
+                        failures.push(format!("Open failed for {}: {}", url, status));
+                    }
+                    false
+                }
+                Ok(None) => true,
+                Err(e) => {
+                    failures.push(format!("Open failed for {}: {}", url, e));
+                    false
+                }
+            }
+        });
+
+        if let Some(failure) = failures.pop() {
+            log::error!("{}", failure);
+            self.status = Some(failure);
+        }
+    }
+
+    fn poll_running(&mut self) {
+        let result = match &self.running {
+            Some(running) => match running.result.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => Err(format!("{} stopped unexpectedly", running.command)),
+            },
+            None => return,
+        };
+
+        let command = self.running.take().unwrap().command;
+
+        match result {
+            Ok(output) => {
+                self.output_pane = Some(OutputPane {
+                    title: format!("!{}", command),
+                    output: output,
+                    scroll: 0,
+                });
+            }
+            Err(e) => {
+                log::error!("{}", e);
+                self.status = Some(e);
+            }
+        }
     }
 
-    pub fn previous_value(&mut self) {
-        if self.current_value_index > 0 {
-            self.current_value_index -= 1;
-        }
+    pub fn clear_status(&mut self) {
+        self.status = None;
     }
 
     pub fn exit_without_value(&mut self) {
@@ -127,15 +983,63 @@ impl App {
     }
 
     pub fn exit_with_value(&mut self) {
-        self.session.value = self.current_value.clone();
-        self.quit();
+        self.session.value = self.get_current_value().map(|value| value.value.trim().to_string());
+        self.finish_with_selection();
+    }
+
+    pub fn confirm(&mut self) {
+        self.mark_visual_range();
+
+        if self.marks.is_empty() {
+            self.run_default_action();
+            return;
+        }
+
+        let mut marks = self.marks.clone();
+        marks.sort_by_key(|mark| {
+            let position = self.display_items.items
+                .iter()
+                .position(|item| item.content.id == mark.item_id)
+                .unwrap_or(usize::MAX);
+
+            (position, mark.path.is_some())
+        });
+
+        self.session.values = marks
+            .into_iter()
+            .map(|mark| SelectedValue {
+                id: mark.item_id,
+                value: mark.value,
+            })
+            .collect();
+        self.session.value = self.session.values.first().map(|selected| selected.value.clone());
+        self.finish_with_selection();
+    }
+
+    pub fn run_default_action(&mut self) {
+        let link = self.get_current_content().and_then(|content| content.get_default_link());
+
+        match link {
+            Some(link) if self.loader.is_some() => self.follow_link(link.value.trim()),
+            Some(link) => {
+                self.session.value = Some(link.value.trim().to_string());
+                self.finish_with_selection();
+            }
+            None => self.exit_with_value(),
+        }
     }
 
     pub fn try_navigate_back(&mut self) {
+        if let Some(input) = self.back_stack.pop() {
+            self.replace_input(input);
+            return;
+        }
+
         if let Some(history) = &self.history {
             if let Some(history_entry) = history.first() {
-                self.session.value = Some(history_entry.url.clone());
-                self.quit();
+                let url = history_entry.url.clone();
+                self.session.value = Some(url.clone());
+                self.finish(AppOutcome::Back(url));
             }
         }
     }
@@ -151,6 +1055,10 @@ impl<T> StatefulList<T> {
     }
 
     pub fn next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+
         let i = match self.state.selected() {
             Some(i) => {
                 if i >= self.items.len() - 1 {
@@ -165,6 +1073,10 @@ impl<T> StatefulList<T> {
     }
 
     pub fn previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
@@ -179,27 +1091,290 @@ impl<T> StatefulList<T> {
     }
 
     pub fn start(&mut self) {
-        self.state.select(Some(0));
+        if !self.items.is_empty() {
+            self.state.select(Some(0));
+        }
     }
 
     pub fn end(&mut self) {
-        self.state.select(Some(self.items.len() - 1));
+        if !self.items.is_empty() {
+            self.state.select(Some(self.items.len() - 1));
+        }
     }
 }
 
 impl App {
+    // Asks the host to load the linked document and shows it in place, keeping
+    // the current one for going back
+    fn follow_link(&mut self, url: &str) {
+        let loaded = match self.loader.as_mut() {
+            Some(loader) => loader(url),
+            None => return,
+        };
+
+        match loaded {
+            Ok(input) => {
+                if let Some(current) = self.input.take() {
+                    self.back_stack.push(current);
+                }
+                self.replace_input(input);
+            }
+            Err(e) => {
+                log::error!("Failed to load {}: {}", url, e);
+                self.status = Some(format!("Failed to load {}: {}", url, e));
+            }
+        }
+    }
+
+    fn replace_input(&mut self, input: Input) {
+        self.zoom_stack.clear();
+        self.collapsed.clear();
+        self.folded_lists.clear();
+        self.marks.clear();
+        self.visual_anchor = None;
+        self.search = None;
+        self.value_cursor = ValueCursor::default();
+
+        self.load_input(&input);
+        self.display_items.start();
+    }
+
     fn init_display_items(&mut self) {
         let mut results = Vec::new();
-        self.input
-            .clone()
-            .unwrap()
-            .content
+        self.get_root()
             .go_down_depth(
                 self.current_depth,
                 &mut results
             );
 
-        self.display_items = StatefulList::<ComplexObject>::with_items(results);
+        let mut items: Vec<ComplexObject> = Vec::new();
+
+        for content in results {
+            if content.is_empty_under(&self.content_filter) {
+                continue;
+            }
+
+            match self.view_mode {
+                ViewMode::Thread => self.flatten_thread(content, 0, &mut items),
+                ViewMode::List | ViewMode::Cards => items.push(ComplexObject {
+                    content: content,
+                    thread_level: 0,
+                    reply_count: 0,
+                }),
+            }
+        }
+
+        self.display_items = StatefulList::<ComplexObject>::with_items(items);
+    }
+
+    fn flatten_thread(&self, content: Content, level: usize, items: &mut Vec<ComplexObject>) {
+        let children = content.children.clone();
+        let is_collapsed = self.collapsed.contains(&content.id);
+
+        items.push(ComplexObject {
+            reply_count: content.count_replies(),
+            content: content,
+            thread_level: level,
+        });
+
+        if !is_collapsed {
+            for child in children {
+                if child.is_empty_under(&self.content_filter) {
+                    continue;
+                }
+
+                self.flatten_thread(child, level + 1, items);
+            }
+        }
+    }
+
+    fn replace_input_with_output(&mut self) {
+        let stdout = match &self.output_pane {
+            Some(pane) => pane.output.stdout.clone(),
+            None => return,
+        };
+
+        match serde_json::from_str::<Input>(&stdout) {
+            Ok(input) => {
+                self.output_pane = None;
+                self.zoom_stack.clear();
+                self.marks.clear();
+                self.visual_anchor = None;
+                self.load_input(&input);
+                self.status = Some("Document replaced with command output".to_string());
+            }
+            Err(e) => {
+                self.status = Some(format!("Output is not a valid document: {}", e));
+            }
+        }
+    }
+
+    fn get_current_item_text(&self) -> Option<String> {
+        let item = self.display_items.items.get(self.display_items.state.selected()?)?;
+
+        Some(self.item_to_lines(item, &self.line_options(None)).to_text())
+    }
+
+    fn get_current_item_json(&self) -> Option<String> {
+        let item = self.display_items.items.get(self.display_items.state.selected()?)?;
+
+        serde_json::to_string_pretty(&item.content).ok()
+    }
+
+    fn get_item_mark_value(&self) -> Option<String> {
+        let item = self.display_items.items.get(self.display_items.state.selected()?)?;
+
+        item.content
+            .get_default_link()
+            .or_else(|| self.get_value_grid().into_iter().flatten().next())
+            .map(|value| value.value.trim().to_string())
+    }
+
+    // The rows between the visual anchor and the selection. The anchor is kept
+    // as an id, so it drops out once its item is no longer listed
+    fn get_visual_range(&self) -> Option<(usize, usize)> {
+        let anchor_id = self.visual_anchor.as_ref()?;
+        let anchor = self.display_items.items
+            .iter()
+            .position(|item| &item.content.id == anchor_id)?;
+        let selected = self.display_items.state.selected()?;
+
+        Some((anchor.min(selected), anchor.max(selected)))
+    }
+
+    fn mark_visual_range(&mut self) {
+        if let Some((start, end)) = self.get_visual_range() {
+            for index in start..=end {
+                self.mark_item(index);
+            }
+        }
+
+        self.visual_anchor = None;
+    }
+
+    fn mark_item(&mut self, index: usize) {
+        let item_id = self.display_items.items[index].content.id.clone();
+
+        if self.marks.iter().any(|mark| mark.item_id == item_id && mark.path.is_none()) {
+            return;
+        }
+
+        let previous = self.display_items.state.selected();
+        self.display_items.state.select(Some(index));
+
+        if let Some(value) = self.get_item_mark_value() {
+            self.marks.push(Mark {
+                item_id: item_id,
+                path: None,
+                value: value,
+            });
+        }
+
+        self.display_items.state.select(previous);
+    }
+
+    fn is_item_marked(&self, index: usize) -> bool {
+        let item_id = &self.display_items.items[index].content.id;
+
+        if self.marks.iter().any(|mark| &mark.item_id == item_id && mark.path.is_none()) {
+            return true;
+        }
+
+        match self.get_visual_range() {
+            Some((start, end)) => index >= start && index <= end,
+            None => false,
+        }
+    }
+
+    fn get_matching_indices(&self) -> Vec<usize> {
+        match &self.search {
+            Some(query) => self.display_items.items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| item.content.matches_query(query))
+                .map(|(index, _)| index)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn jump_to_match(&mut self, forward: bool) {
+        let matches = self.get_matching_indices();
+
+        if matches.is_empty() {
+            return;
+        }
+
+        let target = match (self.display_items.state.selected(), forward) {
+            (Some(selected), true) => matches.iter().find(|index| **index > selected).or(matches.first()),
+            (Some(selected), false) => matches.iter().rev().find(|index| **index < selected).or(matches.last()),
+            (None, _) => matches.first(),
+        };
+
+        self.display_items.state.select(target.cloned());
+    }
+
+    fn move_card_selection(&mut self, offset: isize) {
+        if self.display_items.items.is_empty() {
+            return;
+        }
+
+        let index = match self.display_items.state.selected() {
+            Some(i) => {
+                let target = i as isize + offset;
+
+                if target < 0 || target >= self.display_items.items.len() as isize {
+                    i
+                } else {
+                    target as usize
+                }
+            }
+            None => 0,
+        };
+
+        self.display_items.state.select(Some(index));
+    }
+
+    fn select_by_id(&mut self, id: Option<String>) {
+        if let Some(id) = id {
+            let index = self.display_items.items
+                .iter()
+                .position(|item| item.content.id == id);
+
+            self.display_items.state.select(index);
+        }
+    }
+
+    fn get_root(&self) -> Content {
+        match self.zoom_stack.last() {
+            Some(frame) => frame.content.clone(),
+            None => self.input.clone().unwrap().content,
+        }
+    }
+
+    // Every ancestor of the zoomed content, not only the ones zoomed through
+    fn get_breadcrumb(&self) -> String {
+        let input = match &self.input {
+            Some(input) => input,
+            None => return String::new(),
+        };
+
+        let root = &input.content;
+
+        let path = match self.zoom_stack.last() {
+            Some(frame) => root
+                .path_to(&frame.content.id)
+                .unwrap_or_else(|| vec![root, &frame.content]),
+            None => vec![root],
+        };
+
+        let mut crumbs: Vec<String> = vec![root.get_title().unwrap_or("document".to_string())];
+
+        for content in &path[1..] {
+            crumbs.push(content.get_title().unwrap_or(content.id.clone()));
+        }
+
+        crumbs.join(" > ")
     }
 
     fn get_current_object(&mut self) -> Option<ComplexObject> {
@@ -209,6 +1384,98 @@ impl App {
             None
         }
     }
+
+    fn get_current_content(&mut self) -> Option<Content> {
+        self.get_current_object().map(|item| item.content)
+    }
+
+    fn get_selected_id(&self) -> Option<String> {
+        self.display_items.state
+            .selected()
+            .and_then(|i| self.display_items.items.get(i))
+            .map(|item| item.content.id.clone())
+    }
+
+    fn line_options(&self, highlighted_value: Option<usize>) -> LineOptions<'_> {
+        LineOptions {
+            filter: self.content_filter.clone(),
+            main_content_color: Color::from_str("#111111").unwrap(),
+            text_color: Color::from_str(&self.color_palette.secondary_hex).unwrap(),
+            background_color: Color::from_str(&self.color_palette.background_hex).unwrap(),
+            list_style: self.config.list_style.clone(),
+            folded_lists: &self.folded_lists,
+            table_sorts: &self.table_sorts,
+            highlighted_value: highlighted_value,
+            highlight_color: Color::from_str(HIGHLIGHT_COLOR_HEX).unwrap(),
+            marked_values: Vec::new(),
+            mark_color: Color::from_str(MARK_COLOR_HEX).unwrap(),
+            width: DEFAULT_LINE_WIDTH,
+        }
+    }
+
+    fn item_to_lines(&self, item: &ComplexObject, options: &LineOptions) -> RenderedLines {
+        let mut content = item.content.clone();
+        if self.view_mode == ViewMode::Thread {
+            content.children.clear();
+        }
+
+        let mut rendered = RenderedLines::new();
+        content.to_lines(options, &mut rendered, 0);
+
+        rendered
+    }
+
+    fn get_value_grid(&self) -> Vec<Vec<ContentValue>> {
+        match self.display_items.state.selected().and_then(|i| self.display_items.items.get(i)) {
+            Some(item) => self.item_to_lines(item, &self.line_options(None)).value_grid(),
+            None => Vec::new(),
+        }
+    }
+
+    fn get_value_cursor_position(&self, grid: &Vec<Vec<ContentValue>>) -> Option<(usize, usize)> {
+        if grid.is_empty() {
+            return None;
+        }
+
+        if self.value_cursor.item_id.is_none() || self.value_cursor.item_id != self.get_selected_id() {
+            return Some((0, 0));
+        }
+
+        let row = self.value_cursor.row.min(grid.len() - 1);
+        let column = self.value_cursor.column.min(grid[row].len() - 1);
+
+        Some((row, column))
+    }
+
+    fn move_value_cursor(&mut self, rows: isize, columns: isize) {
+        let grid = self.get_value_grid();
+
+        if let Some((row, column)) = self.get_value_cursor_position(&grid) {
+            let row = (row as isize + rows).clamp(0, grid.len() as isize - 1) as usize;
+            let column = (column as isize + columns).clamp(0, grid[row].len() as isize - 1) as usize;
+
+            self.value_cursor = ValueCursor {
+                item_id: self.get_selected_id(),
+                row: row,
+                column: column,
+            };
+        }
+    }
+
+    fn get_highlighted_ordinal(&self) -> Option<usize> {
+        let grid = self.get_value_grid();
+        let (row, column) = self.get_value_cursor_position(&grid)?;
+
+        Some(grid[..row].iter().map(|values| values.len()).sum::<usize>() + column)
+    }
+
+    fn get_highlighted_path(&self) -> Option<ValuePath> {
+        let item = self.display_items.items.get(self.display_items.state.selected()?)?;
+        let paths = self.item_to_lines(item, &self.line_options(None)).path_grid();
+        let (row, column) = self.get_value_cursor_position(&self.get_value_grid())?;
+
+        Some(paths[row][column].clone())
+    }
 }
 
 impl Widget for &mut App {
@@ -216,12 +1483,14 @@ impl Widget for &mut App {
         let vertical = Layout::vertical([
             Constraint::Length(1),
             Constraint::Min(0),
+            Constraint::Length(1),
         ]);
 
-        let [header_area, body_area] = vertical.areas(area);
+        let [header_area, body_area, footer_area] = vertical.areas(area);
 
         self.render_header(header_area, buf);
         self.render_body(body_area, buf);
+        self.render_footer(footer_area, buf);
     }
 }
 
@@ -229,7 +1498,7 @@ impl App {
     fn render_header(&mut self, area: Rect, buf: &mut Buffer) {
         let text_color: Color = Color::from_str("#111111").unwrap();
 
-        let text = "Placeholder title";
+        let text = self.get_breadcrumb();
 
         let span: Span = Span::styled(
             text,
@@ -249,60 +1518,274 @@ impl App {
                     )
             )
             .render(area, buf);
+
+        let filter_span: Span = Span::styled(
+            format!("filter: {} ", self.content_filter.label()),
+            Style::new()
+                .fg(text_color)
+        ).into();
+
+        Paragraph::new(filter_span)
+            .alignment(Alignment::Right)
+            .render(area, buf);
+    }
+
+    fn render_footer(&mut self, area: Rect, buf: &mut Buffer) {
+        let text_color: Color = Color::from_str("#111111").unwrap();
+        let background_color: Color = Color::from_str(&self.color_palette.background_hex).unwrap();
+
+        let text = match &self.prompt {
+            Some(prompt) => match prompt.kind {
+                PromptKind::Search => format!("/{}", prompt.text),
+                PromptKind::Command => format!(":{}", prompt.text),
+            },
+            None => {
+                let mut parts: Vec<String> = Vec::new();
+
+                if let Some(status) = &self.status {
+                    parts.push(status.clone());
+                }
+
+                if let Some(running) = &self.running {
+                    parts.push(format!("running: {}", running.command));
+                }
+
+                if self.visual_anchor.is_some() {
+                    parts.push("-- VISUAL --".to_string());
+                }
+
+                if !self.marks.is_empty() {
+                    parts.push(format!("{} marked", self.marks.len()));
+                }
+
+                if let Some(query) = &self.search {
+                    parts.push(format!("search: {} ({} matches)", query, self.get_matching_indices().len()));
+                }
+
+                parts.join("  ")
+            }
+        };
+
+        Paragraph::new(Span::styled(text, Style::new().fg(text_color)))
+            .block(
+                Block::default()
+                    .style(
+                        Style::default()
+                            .bg(background_color)
+                    )
+            )
+            .render(area, buf);
     }
 
     fn render_body(&mut self, area: Rect, buf: &mut Buffer) {
+        match self.view_mode {
+            ViewMode::Cards => self.render_cards(area, buf),
+            _ => self.render_list(area, buf),
+        }
+
+        if self.output_pane.is_some() {
+            self.render_output_pane(area, buf);
+        }
+    }
+
+    fn render_output_pane(&mut self, area: Rect, buf: &mut Buffer) {
+        let text_color: Color = Color::from_str("#111111").unwrap();
+        let border_color: Color = Color::from_str(&self.color_palette.primary_hex).unwrap();
+        let background_color: Color = Color::from_str(&self.color_palette.background_hex).unwrap();
+        let error_color: Color = Color::from_str("#CC0000").unwrap();
+
+        let pane = self.output_pane.as_ref().unwrap();
+
+        let mut lines: Vec<Line> = pane.output.stdout
+            .lines()
+            .map(|line| Line::from(Span::styled(line.to_string(), Style::new().fg(text_color))))
+            .collect();
+
+        lines.extend(
+            pane.output.stderr
+                .lines()
+                .map(|line| Line::from(Span::styled(line.to_string(), Style::new().fg(error_color))))
+        );
+
+        let status_color = if pane.output.success { text_color } else { error_color };
+
+        let block = Block::bordered()
+            .border_style(Style::new().fg(border_color))
+            .title(Span::styled(pane.title.clone(), Style::new().fg(text_color).add_modifier(Modifier::BOLD)))
+            .title_bottom(Line::from(vec![
+                Span::styled(format!(" {} ", pane.output.status), Style::new().fg(status_color)),
+                Span::styled("q close  r replace document ", Style::new().fg(text_color)),
+            ]))
+            .style(Style::new().bg(background_color));
+
+        let pane_area = area.inner(&Margin { horizontal: 2, vertical: 1 });
+
+        Clear.render(pane_area, buf);
+
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((pane.scroll, 0))
+            .render(pane_area, buf);
+    }
+
+    fn render_cards(&mut self, area: Rect, buf: &mut Buffer) {
         let main_content_color: Color = Color::from_str("#111111").unwrap();
         let text_color: Color = Color::from_str(&self.color_palette.secondary_hex).unwrap();
+        let highlight_color: Color = Color::from_str(&self.color_palette.primary_hex).unwrap();
+        let background_color: Color = Color::from_str(&self.color_palette.background_hex).unwrap();
+
+        Block::new()
+            .style(Style::new().bg(background_color))
+            .render(area, buf);
+
+        let columns = (area.width / CARD_MIN_WIDTH).max(1) as usize;
+        let visible_rows = (area.height / CARD_HEIGHT).max(1) as usize;
+        let card_width = area.width / columns as u16;
+
+        self.card_columns = columns;
+
+        if let Some(selected) = self.display_items.state.selected() {
+            let selected_row = selected / columns;
+
+            if selected_row < self.card_row_offset {
+                self.card_row_offset = selected_row;
+            } else if selected_row >= self.card_row_offset + visible_rows {
+                self.card_row_offset = selected_row + 1 - visible_rows;
+            }
+        }
+
+        let first = self.card_row_offset * columns;
+
+        for (position, item) in self.display_items.items.iter().enumerate().skip(first).take(visible_rows * columns) {
+            let row = ((position - first) / columns) as u16;
+            let column = ((position - first) % columns) as u16;
+
+            let card_area = Rect {
+                x: area.x + column * card_width,
+                y: area.y + row * CARD_HEIGHT,
+                width: card_width,
+                height: CARD_HEIGHT,
+            }.intersection(area);
+
+            let is_selected = self.display_items.state.selected() == Some(position);
+            let border_color = if is_selected { highlight_color } else { text_color };
+
+            let title = item.content.values
+                .iter()
+                .filter(|value| self.content_filter.includes(value))
+                .find(|value| value.meta.is_title)
+                .map(|value| value.value.trim().to_string())
+                .unwrap_or_default();
+
+            let primary: Vec<&str> = item.content.values
+                .iter()
+                .filter(|value| self.content_filter.includes(value))
+                .filter(|value| value.meta.is_primary_content && !value.meta.is_title)
+                .map(|value| value.value.trim())
+                .collect();
+
+            let body = if primary.is_empty() {
+                item.content.values
+                    .iter()
+                    .filter(|value| self.content_filter.includes(value))
+                    .filter(|value| !value.meta.is_title && !value.meta.is_url)
+                    .map(|value| value.value.trim())
+                    .collect::<Vec<&str>>()
+                    .join(" ")
+            } else {
+                primary.join(" ")
+            };
+
+            let mut block = Block::bordered()
+                .border_style(Style::new().fg(border_color))
+                .title(Span::styled(title, Style::new().fg(main_content_color).add_modifier(Modifier::BOLD)))
+                .style(Style::new().bg(background_color));
+
+            if is_selected {
+                block = block.border_type(BorderType::Thick);
+            }
+
+            Paragraph::new(body)
+                .style(Style::new().fg(main_content_color))
+                .wrap(Wrap { trim: true })
+                .block(block)
+                .render(card_area, buf);
+        }
+    }
+
+    fn render_list(&mut self, area: Rect, buf: &mut Buffer) {
+        let text_color: Color = Color::from_str(&self.color_palette.secondary_hex).unwrap();
+        let mark_color: Color = Color::from_str(&self.color_palette.primary_hex).unwrap();
         let background_color: Color = Color::from_str(&self.color_palette.background_hex).unwrap();
 
+        let selected_item_index = self.display_items.state.selected();
+        let highlighted_value = self.get_highlighted_ordinal();
+
         let items: Vec<RListItem> = self.display_items.items
-            .clone()
             .iter()
             .enumerate()
             .map(|(index, item)| {
-                let mut lines: Vec<Line> = Vec::new();
-
-                item.to_lines(
-                    &self.should_display_primary_content,
-                    &main_content_color,
-                    &text_color,
-                    &background_color,
-                    &mut lines,
-                    0,
-                );
+                let mut line_options = if selected_item_index == Some(index) {
+                    self.line_options(highlighted_value)
+                } else {
+                    self.line_options(None)
+                };
 
-                if let Some(selected_item_index) = self.display_items.state.selected() {
-                    if selected_item_index == index {
-                        let mut current_index = 0;
-                        for line in lines.iter_mut() {
-                            for span in line.spans.iter_mut() {
-                                if !span.content.trim().is_empty() {
-                                    if self.current_value_index == current_index {
-                                        self.current_value = Some(span.content.clone().to_string());
-                                        let color = Color::from_str("#00FF00").unwrap();
-                                        span.style = span.style.bg(color);
-                                    }
-                                    current_index += 1;
-                                }
-                            }
-                        }
+                line_options.marked_values = self.marks
+                    .iter()
+                    .filter_map(|mark| mark.path.clone())
+                    .collect();
+
+                let mut lines: Vec<Line> = self.item_to_lines(item, &line_options).lines;
+
+                // A thread parent can be kept only for its matching replies.
+                // It still needs a row, or the list and the selection drift apart
+                if lines.is_empty() {
+                    lines.push(
+                        Line::from(
+                            Span::styled(
+                                "[hidden by filter]".to_string(),
+                                Style::new().fg(text_color).add_modifier(Modifier::ITALIC),
+                            )
+                        )
+                    );
+                }
+
+                if self.is_item_marked(index) {
+                    for line in lines.iter_mut() {
+                        line.spans.insert(0, Span::styled("▌", Style::new().fg(mark_color)));
                     }
                 }
 
-                if lines.len() > 0 {
+                if self.view_mode == ViewMode::Thread && item.reply_count > 0 && self.collapsed.contains(&item.content.id) {
+                    let noun = if item.reply_count == 1 { "reply" } else { "replies" };
+
                     lines.push(
-                        Line::from("".to_string())
+                        Line::from(
+                            Span::styled(
+                                format!("[+{} {}]", item.reply_count, noun),
+                                Style::new().fg(text_color).add_modifier(Modifier::ITALIC),
+                            )
+                        )
                     );
                 }
 
+                lines.push(
+                    Line::from("".to_string())
+                );
+
+                if item.thread_level > 0 {
+                    let guides = "│ ".repeat(item.thread_level);
+
+                    for line in lines.iter_mut() {
+                        line.spans.insert(0, Span::styled(guides.clone(), Style::new().fg(text_color)));
+                    }
+                }
+
                 lines.truncate(30);
 
                 lines
             })
-            .filter(|item| {
-                item.len() > 0
-            })
             .map(|item| {
                 RListItem::new(item)
             })
@@ -0,0 +1,183 @@
+use std::io::{Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+// Terminals and multiplexers commonly drop OSC 52 payloads above this size
+const OSC52_MAX_BYTES: usize = 74_994;
+
+// How long to wait for a clipboard command to report failure before assuming
+// it is still working and letting it finish in the background
+const COMMAND_WAIT: Duration = Duration::from_millis(300);
+
+const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// A configured command is tried first, since writing OSC 52 succeeds whether
+// or not the terminal supports it. The sequence goes to out, the terminal the
+// host draws on, and can't be sent without one
+pub fn copy(text: &str, command: &Option<String>, out: Option<&mut dyn Write>) -> Result<String, String> {
+    log::trace!("In copy");
+
+    let command_error = match command {
+        Some(command) => match copy_command(text, command) {
+            Ok(()) => return Ok(command.clone()),
+            Err(e) => e,
+        },
+        None => return copy_osc52(text, out).map(|_| "OSC 52".to_string()),
+    };
+
+    log::debug!("Clipboard command failed: {}", command_error);
+
+    copy_osc52(text, out)
+        .map(|_| "OSC 52".to_string())
+        .map_err(|e| format!("{}; {}", command_error, e))
+}
+
+fn copy_osc52(text: &str, out: Option<&mut dyn Write>) -> Result<(), String> {
+    let out = out.ok_or_else(|| "no terminal to send OSC 52 to".to_string())?;
+    let encoded = base64_encode(text.as_bytes());
+
+    if encoded.len() > OSC52_MAX_BYTES {
+        return Err(format!("{} bytes is too large for OSC 52", text.len()));
+    }
+
+    let mut sequence = format!("\x1b]52;c;{}\x07", encoded);
+
+    // tmux only forwards escape sequences to the outer terminal when wrapped
+    if std::env::var("TMUX").is_ok() {
+        sequence = format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"));
+    }
+
+    out.write_all(sequence.as_bytes()).map_err(|e| e.to_string())?;
+    out.flush().map_err(|e| e.to_string())
+}
+
+fn copy_command(text: &str, command: &str) -> Result<(), String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to run {}: {}", command, e))?;
+
+    // Feeding and waiting happen off the UI thread, a command that takes its
+    // time only delays the report of whether it failed
+    let (sender, receiver) = mpsc::channel();
+    let text = text.to_string();
+
+    std::thread::spawn(move || {
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Err(e) = stdin.write_all(text.as_bytes()) {
+                log::debug!("Failed to write to clipboard command: {}", e);
+            }
+        }
+
+        let _ = sender.send(child.wait());
+    });
+
+    match receiver.recv_timeout(COMMAND_WAIT) {
+        Ok(Ok(status)) if status.success() => Ok(()),
+        Ok(Ok(status)) => Err(format!("{} exited with {}", command, status)),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => {
+            log::debug!("{} is still running, leaving it to finish", command);
+            Ok(())
+        }
+    }
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[((n >> (18 - i * 6)) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_pads_partial_chunks() {
+        let cases: [(&str, &str); 7] = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(base64_encode(input.as_bytes()), expected, "encoding {:?}", input);
+        }
+    }
+
+    #[test]
+    fn base64_uses_the_full_alphabet() {
+        assert_eq!(base64_encode(&[0xfb, 0xff, 0xbf]), "+/+/");
+        assert_eq!(base64_encode("é".as_bytes()), "w6k=");
+    }
+
+    #[test]
+    fn command_is_used_before_osc52() {
+        let path = std::env::temp_dir().join(format!("tooey-clipboard-{}", std::process::id()));
+        let command = Some(format!("cat > '{}'", path.display()));
+        let mut out = Vec::new();
+
+        let method = copy("copied text", &command, Some(&mut out)).unwrap();
+
+        // The command may still be finishing in the background
+        for _ in 0..50 {
+            if std::fs::read_to_string(&path).map(|text| text == "copied text").unwrap_or(false) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        assert_eq!(method, command.unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "copied text");
+        assert!(out.is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn failing_command_falls_back_to_osc52() {
+        let mut out = Vec::new();
+
+        let method = copy("hi", &Some("exit 3".to_string()), Some(&mut out)).unwrap();
+
+        assert_eq!(method, "OSC 52");
+        assert!(String::from_utf8(out).unwrap().contains("\x1b]52;c;aGk=\x07"));
+    }
+
+    #[test]
+    fn osc52_needs_somewhere_to_go() {
+        assert_eq!(copy("hi", &None, None), Err("no terminal to send OSC 52 to".to_string()));
+    }
+
+    #[test]
+    fn slow_command_does_not_block() {
+        let started = std::time::Instant::now();
+
+        copy("hi", &Some("sleep 5".to_string()), Some(&mut Vec::new())).unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,82 @@
+use crate::export::{ExportFormat, ExportScope};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PipeSource {
+    Value,
+    Text,
+    Json,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Pipe {
+        source: PipeSource,
+        command: String,
+    },
+    Export {
+        format: ExportFormat,
+        path: String,
+        scope: ExportScope,
+    },
+}
+
+// Parses a command line entered after ":". A leading "!" is shorthand for
+// piping the highlighted value, "pipe SOURCE CMD" picks the source explicitly
+pub fn parse_command(text: &str) -> Result<Command, String> {
+    let text = text.trim();
+
+    if let Some(command) = text.strip_prefix('!') {
+        return pipe(PipeSource::Value, command);
+    }
+
+    let (name, rest) = text.split_once(' ').unwrap_or((text, ""));
+
+    match name {
+        "pipe" => {
+            let (source, command) = rest.trim().split_once(' ').unwrap_or((rest.trim(), ""));
+
+            let source = match source {
+                "value" => PipeSource::Value,
+                "text" => PipeSource::Text,
+                "json" => PipeSource::Json,
+                _ => return Err(format!("Unknown pipe source: {}", source)),
+            };
+
+            pipe(source, command)
+        }
+        "export" => {
+            let arguments: Vec<&str> = rest.split_whitespace().collect();
+
+            if arguments.len() < 2 || arguments.len() > 3 {
+                return Err("Usage: export text|md|html PATH [depth|document]".to_string());
+            }
+
+            let format = ExportFormat::parse(arguments[0])
+                .ok_or(format!("Unknown export format: {}", arguments[0]))?;
+
+            let scope = match arguments.get(2) {
+                Some(scope) => ExportScope::parse(scope).ok_or(format!("Unknown export scope: {}", scope))?,
+                None => ExportScope::Depth,
+            };
+
+            Ok(Command::Export {
+                format: format,
+                path: arguments[1].to_string(),
+                scope: scope,
+            })
+        }
+        "" => Err("Empty command".to_string()),
+        _ => Err(format!("Unknown command: {}", name)),
+    }
+}
+
+fn pipe(source: PipeSource, command: &str) -> Result<Command, String> {
+    if command.trim().is_empty() {
+        return Err("No shell command given".to_string());
+    }
+
+    Ok(Command::Pipe {
+        source: source,
+        command: command.trim().to_string(),
+    })
+}
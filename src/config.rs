@@ -0,0 +1,155 @@
+use serde::{Serialize, Deserialize};
+use std::path::{PathBuf};
+
+use crate::opener::{OpenRule};
+use crate::error::{Errors};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum InitialDepth {
+    Fixed(usize),
+    Auto,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ListStyle {
+    Bullet,
+    Numbered,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewportHeight {
+    Lines(u16),
+    Percent(u16),
+}
+
+const DEFAULT_PRIMARY_COLOR_HEX: &str = "#FF6600";
+const DEFAULT_SECONDARY_COLOR_HEX: &str = "#828282";
+const DEFAULT_BACKGROUND_COLOR_HEX: &str = "#F6F6EF";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ColorPalette {
+    pub primary_hex: String,
+    pub secondary_hex: String,
+    pub background_hex: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Config {
+    pub initial_depth: InitialDepth,
+    pub list_style: ListStyle,
+    pub clipboard_command: Option<String>,
+    pub opener: Option<String>,
+    pub open_rules: Vec<OpenRule>,
+    pub height: Option<ViewportHeight>,
+    pub theme: ColorPalette,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            initial_depth: InitialDepth::Auto,
+            list_style: ListStyle::Bullet,
+            clipboard_command: None,
+            opener: None,
+            open_rules: Vec::new(),
+            height: None,
+            theme: ColorPalette::default(),
+        }
+    }
+}
+
+impl Default for ColorPalette {
+    fn default() -> Self {
+        ColorPalette {
+            primary_hex: DEFAULT_PRIMARY_COLOR_HEX.to_string(),
+            secondary_hex: DEFAULT_SECONDARY_COLOR_HEX.to_string(),
+            background_hex: DEFAULT_BACKGROUND_COLOR_HEX.to_string(),
+        }
+    }
+}
+
+impl ColorPalette {
+    pub fn is_valid(&self) -> bool {
+        [&self.primary_hex, &self.secondary_hex, &self.background_hex]
+            .iter()
+            .all(|hex| hex.len() == 7 && hex.starts_with('#') && u32::from_str_radix(&hex[1..], 16).is_ok())
+    }
+}
+
+impl Config {
+    // $TOOEY_CONFIG, or config.json in the tooey directory under the XDG config home
+    pub fn default_path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var("TOOEY_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+
+        let config_home = std::env::var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|_| std::env::var("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .ok()?;
+
+        Some(config_home.join("tooey").join("config.json"))
+    }
+
+    pub fn load() -> Result<Config, Errors> {
+        match Config::default_path() {
+            Some(path) if path.exists() => {
+                log::debug!("Loading config from {}", path.display());
+
+                let contents = std::fs::read_to_string(&path)
+                    .map_err(|e| Errors::ConfigError(format!("{}: {}", path.display(), e)))?;
+
+                serde_json::from_str(&contents)
+                    .map_err(|e| Errors::ConfigError(format!("{}: {}", path.display(), e)))
+            }
+            _ => Ok(Config::default()),
+        }
+    }
+}
+
+impl InitialDepth {
+    pub fn parse(value: &str) -> Option<InitialDepth> {
+        if value.trim() == "auto" {
+            return Some(InitialDepth::Auto);
+        }
+
+        value.trim().parse::<usize>().ok().map(InitialDepth::Fixed)
+    }
+}
+
+impl ListStyle {
+    pub fn parse(value: &str) -> Option<ListStyle> {
+        match value.trim() {
+            "bullet" => Some(ListStyle::Bullet),
+            "numbered" => Some(ListStyle::Numbered),
+            _ => None,
+        }
+    }
+}
+
+impl ViewportHeight {
+    pub fn parse(value: &str) -> Option<ViewportHeight> {
+        let value = value.trim();
+
+        let height = match value.strip_suffix('%') {
+            Some(percent) => ViewportHeight::Percent(percent.parse::<u16>().ok().filter(|p| *p <= 100)?),
+            None => ViewportHeight::Lines(value.parse::<u16>().ok()?),
+        };
+
+        Some(height)
+    }
+
+    pub fn lines(&self, terminal_rows: u16) -> u16 {
+        let lines = match self {
+            ViewportHeight::Lines(lines) => *lines,
+            ViewportHeight::Percent(percent) => (terminal_rows as u32 * *percent as u32 / 100) as u16,
+        };
+
+        lines.clamp(1, terminal_rows.max(1))
+    }
+}
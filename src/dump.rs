@@ -0,0 +1,129 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use ratatui::prelude::*;
+
+use crate::config::{ListStyle};
+use crate::input::{Content, ContentFilter, LineOptions, RenderedLines};
+
+#[derive(Clone, Debug)]
+pub struct DumpOptions {
+    pub color: bool,
+    pub width: usize,
+    pub numbers: bool,
+}
+
+pub fn dump(
+    contents: &Vec<Content>,
+    options: &DumpOptions,
+    filter: &ContentFilter,
+    list_style: &ListStyle,
+    secondary_hex: &str,
+) -> String {
+    log::trace!("In dump");
+
+    let folded_lists = HashSet::new();
+    let table_sorts = HashMap::new();
+
+    // The document's own text keeps the terminal's default colours so it stays
+    // readable on both light and dark backgrounds
+    let line_options = LineOptions {
+        filter: filter.clone(),
+        main_content_color: Color::Reset,
+        text_color: Color::from_str(secondary_hex).unwrap_or(Color::Reset),
+        background_color: Color::Reset,
+        list_style: list_style.clone(),
+        folded_lists: &folded_lists,
+        table_sorts: &table_sorts,
+        highlighted_value: None,
+        highlight_color: Color::Reset,
+        marked_values: Vec::new(),
+        mark_color: Color::Reset,
+        width: options.width,
+    };
+
+    let items: Vec<&Content> = contents
+        .iter()
+        .filter(|content| !content.is_empty_under(filter))
+        .collect();
+
+    let number_width = format!("{}. ", items.len()).len();
+
+    let mut result = String::new();
+
+    for (index, content) in items.iter().enumerate() {
+        let mut rendered = RenderedLines::new();
+        content.to_lines(&line_options, &mut rendered, 0);
+
+        for (line_index, line) in rendered.lines.iter().enumerate() {
+            if options.numbers {
+                if line_index == 0 {
+                    result.push_str(&format!("{:<width$}", format!("{}.", index + 1), width = number_width));
+                } else {
+                    result.push_str(&" ".repeat(number_width));
+                }
+            }
+
+            if options.color {
+                result.push_str(&line_to_ansi(line));
+            } else {
+                result.push_str(
+                    line.spans
+                        .iter()
+                        .map(|span| span.content.as_ref())
+                        .collect::<String>()
+                        .trim_end()
+                );
+            }
+
+            let trimmed = result.trim_end_matches(' ').len();
+            result.truncate(trimmed);
+            result.push('\n');
+        }
+
+        result.push('\n');
+    }
+
+    result
+}
+
+fn line_to_ansi(line: &Line) -> String {
+    let mut result = String::new();
+
+    for span in &line.spans {
+        let codes = style_codes(&span.style);
+
+        if codes.is_empty() || span.content.trim().is_empty() {
+            result.push_str(&span.content);
+        } else {
+            result.push_str(&format!("\x1b[{}m{}\x1b[0m", codes.join(";"), span.content));
+        }
+    }
+
+    result.trim_end().to_string()
+}
+
+fn style_codes(style: &Style) -> Vec<String> {
+    let mut codes: Vec<String> = Vec::new();
+
+    if style.add_modifier.contains(Modifier::BOLD) {
+        codes.push("1".to_string());
+    }
+
+    if style.add_modifier.contains(Modifier::ITALIC) {
+        codes.push("3".to_string());
+    }
+
+    if style.add_modifier.contains(Modifier::UNDERLINED) {
+        codes.push("4".to_string());
+    }
+
+    if let Some(Color::Rgb(r, g, b)) = style.fg {
+        codes.push(format!("38;2;{};{};{}", r, g, b));
+    }
+
+    if let Some(Color::Rgb(r, g, b)) = style.bg {
+        codes.push(format!("48;2;{};{};{}", r, g, b));
+    }
+
+    codes
+}
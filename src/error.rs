@@ -1,7 +1,182 @@
 use serde::{Serialize, Deserialize};
+use std::fmt;
+
+const SNIPPET_WIDTH: usize = 60;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum Errors {
-    UnexpectedError,
-    DeserializationError,
+    UnexpectedError(String),
+    DeserializationError {
+        message: String,
+        line: usize,
+        column: usize,
+        path: String,
+        snippet: String,
+    },
+    TerminalError(String),
+    ConfigError(String),
+    InvalidInput(String),
+}
+
+impl fmt::Display for Errors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Errors::UnexpectedError(message) => write!(f, "unexpected error: {}", message),
+            Errors::DeserializationError { message, line, column, path, snippet } => {
+                write!(f, "invalid document at line {}, column {}", line, column)?;
+
+                if !path.is_empty() {
+                    write!(f, " ({})", path)?;
+                }
+
+                write!(f, ": {}", message)?;
+
+                if !snippet.is_empty() {
+                    write!(f, "\n{}", snippet)?;
+                }
+
+                Ok(())
+            }
+            Errors::TerminalError(message) => write!(f, "terminal error: {}", message),
+            Errors::ConfigError(message) => write!(f, "invalid config: {}", message),
+            Errors::InvalidInput(message) => write!(f, "invalid input: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Errors {}
+
+impl Errors {
+    pub fn from_serde(json: &str, error: &serde_json::Error) -> Errors {
+        // serde_json appends the position to the message, it is reported separately
+        let message = error.to_string();
+        let message = match message.rfind(" at line ") {
+            Some(index) => message[..index].to_string(),
+            None => message,
+        };
+
+        let offset = byte_offset(json, error.line(), error.column());
+
+        Errors::DeserializationError {
+            message: message,
+            line: error.line(),
+            column: error.column(),
+            path: json_path(&json[..offset]),
+            snippet: snippet(json, error.line(), error.column()),
+        }
+    }
+}
+
+fn byte_offset(json: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = json
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(|line| line.len())
+        .sum();
+
+    let line_text = json[line_start..].split('\n').next().unwrap_or("");
+
+    let mut offset = line_start + column.min(line_text.len());
+    while !json.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    offset
+}
+
+enum Frame {
+    Object { key: Option<String>, expecting_key: bool },
+    Array { index: usize },
+}
+
+// Walks the text before the error and reports where the parser had got to,
+// such as $.content.children[2].values
+fn json_path(prefix: &str) -> String {
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut chars = prefix.chars();
+    let mut started = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                started = true;
+                stack.push(Frame::Object { key: None, expecting_key: true });
+            }
+            '[' => {
+                started = true;
+                stack.push(Frame::Array { index: 0 });
+            }
+            '}' | ']' => {
+                stack.pop();
+            }
+            ',' => match stack.last_mut() {
+                Some(Frame::Object { expecting_key, .. }) => *expecting_key = true,
+                Some(Frame::Array { index }) => *index += 1,
+                None => {}
+            },
+            ':' => {
+                if let Some(Frame::Object { expecting_key, .. }) = stack.last_mut() {
+                    *expecting_key = false;
+                }
+            }
+            '"' => {
+                let mut text = String::new();
+                let mut escaped = false;
+
+                for c in chars.by_ref() {
+                    if escaped {
+                        text.push(c);
+                        escaped = false;
+                    } else if c == '\\' {
+                        escaped = true;
+                    } else if c == '"' {
+                        break;
+                    } else {
+                        text.push(c);
+                    }
+                }
+
+                if let Some(Frame::Object { key, expecting_key: true }) = stack.last_mut() {
+                    *key = Some(text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !started {
+        return String::new();
+    }
+
+    let mut path = String::from("$");
+
+    for frame in &stack {
+        match frame {
+            Frame::Object { key: Some(key), .. } => {
+                path.push('.');
+                path.push_str(key);
+            }
+            Frame::Object { key: None, .. } => {}
+            Frame::Array { index } => path.push_str(&format!("[{}]", index)),
+        }
+    }
+
+    path
+}
+
+fn snippet(json: &str, line: usize, column: usize) -> String {
+    let line_text = match json.lines().nth(line.saturating_sub(1)) {
+        Some(line_text) => line_text,
+        None => return String::new(),
+    };
+
+    let chars: Vec<char> = line_text.chars().collect();
+    let column = column.saturating_sub(1).min(chars.len());
+
+    let start = column.saturating_sub(SNIPPET_WIDTH / 2);
+    let end = (start + SNIPPET_WIDTH).min(chars.len());
+
+    let excerpt: String = chars[start..end].iter().collect();
+
+    format!("    {}\n    {}^", excerpt, " ".repeat(column - start))
 }
@@ -0,0 +1,427 @@
+use std::collections::{HashMap, HashSet};
+use ratatui::prelude::*;
+
+use crate::config::{ListStyle};
+use crate::input::{Content, ContentFilter, ContentValue, LineOptions, RenderedLines, DEFAULT_LINE_WIDTH};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExportFormat {
+    Text,
+    Markdown,
+    Html,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExportScope {
+    Document,
+    Depth,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Option<ExportFormat> {
+        match value.trim() {
+            "text" | "txt" => Some(ExportFormat::Text),
+            "markdown" | "md" => Some(ExportFormat::Markdown),
+            "html" => Some(ExportFormat::Html),
+            _ => None,
+        }
+    }
+}
+
+impl ExportScope {
+    pub fn parse(value: &str) -> Option<ExportScope> {
+        match value.trim() {
+            "document" => Some(ExportScope::Document),
+            "depth" => Some(ExportScope::Depth),
+            _ => None,
+        }
+    }
+}
+
+pub fn export(
+    contents: &Vec<Content>,
+    format: &ExportFormat,
+    filter: &ContentFilter,
+    list_style: &ListStyle,
+) -> String {
+    log::trace!("In export");
+
+    match format {
+        ExportFormat::Text => to_text(contents, filter, list_style),
+        ExportFormat::Markdown => {
+            let mut result = String::new();
+
+            for content in contents {
+                if content.is_empty_under(filter) {
+                    continue;
+                }
+
+                to_markdown(content, filter, list_style, 2, &mut result);
+                result.push('\n');
+            }
+
+            result.trim_end().to_string() + "\n"
+        }
+        ExportFormat::Html => {
+            let title = contents
+                .first()
+                .and_then(|content| content.get_title())
+                .unwrap_or("tooey export".to_string());
+
+            let mut body = String::new();
+
+            for content in contents {
+                if content.is_empty_under(filter) {
+                    continue;
+                }
+
+                body.push_str("<section>\n");
+                to_html(content, filter, list_style, 2, &mut body);
+                body.push_str("</section>\n");
+            }
+
+            format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+                escape_html(&title),
+                body,
+            )
+        }
+    }
+}
+
+fn to_text(contents: &Vec<Content>, filter: &ContentFilter, list_style: &ListStyle) -> String {
+    let folded_lists = HashSet::new();
+    let table_sorts = HashMap::new();
+
+    let options = LineOptions {
+        filter: filter.clone(),
+        main_content_color: Color::Reset,
+        text_color: Color::Reset,
+        background_color: Color::Reset,
+        list_style: list_style.clone(),
+        folded_lists: &folded_lists,
+        table_sorts: &table_sorts,
+        highlighted_value: None,
+        highlight_color: Color::Reset,
+        marked_values: Vec::new(),
+        mark_color: Color::Reset,
+        width: DEFAULT_LINE_WIDTH,
+    };
+
+    contents
+        .iter()
+        .filter(|content| !content.is_empty_under(filter))
+        .map(|content| {
+            let mut rendered = RenderedLines::new();
+            content.to_lines(&options, &mut rendered, 0);
+            rendered.to_text()
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n") + "\n"
+}
+
+fn to_markdown(
+    content: &Content,
+    filter: &ContentFilter,
+    list_style: &ListStyle,
+    heading_level: usize,
+    result: &mut String,
+) {
+    let values = content.sorted_values(filter);
+
+    for value in values.iter().filter(|value| value.meta.is_title) {
+        let text = match title_link(content, filter) {
+            Some(link) => format!("[{}]({})", escape_markdown(value.value.trim()), escape_markdown_url(link.value.trim())),
+            None => escape_markdown(value.value.trim()),
+        };
+
+        result.push_str(&format!("{} {}\n\n", "#".repeat(heading_level.min(6)), text));
+    }
+
+    let body: Vec<String> = values
+        .iter()
+        .filter(|value| !value.meta.is_title)
+        .filter(|value| !(value.meta.is_url && title_link(content, filter).is_some()))
+        .map(markdown_value)
+        .collect();
+
+    if !body.is_empty() {
+        result.push_str(&body.join(" "));
+        result.push_str("\n\n");
+    }
+
+    for child in &content.inner_content {
+        to_markdown(child, filter, list_style, heading_level + 1, result);
+    }
+
+    markdown_lists(content, filter, list_style, heading_level, result);
+
+    for child in content.children.iter().filter(|child| !child.is_empty_under(filter)) {
+        markdown_reply(child, filter, list_style, heading_level + 1, result);
+    }
+}
+
+// Replies are quoted, which also keeps them apart from the text above
+fn markdown_reply(
+    content: &Content,
+    filter: &ContentFilter,
+    list_style: &ListStyle,
+    heading_level: usize,
+    result: &mut String,
+) {
+    let mut reply = String::new();
+    to_markdown(content, filter, list_style, heading_level, &mut reply);
+
+    for line in reply.trim_end().lines() {
+        result.push_str(&format!("> {}\n", line).replace("> \n", ">\n"));
+    }
+    result.push('\n');
+}
+
+fn markdown_lists(
+    content: &Content,
+    filter: &ContentFilter,
+    list_style: &ListStyle,
+    heading_level: usize,
+    result: &mut String,
+) {
+    for list in &content.lists {
+        let items = list.iter().filter(|item| !item.is_empty_under(filter));
+
+        for (index, item) in items.enumerate() {
+            let marker = match list_style {
+                ListStyle::Bullet => "- ".to_string(),
+                ListStyle::Numbered => format!("{}. ", index + 1),
+            };
+
+            result.push_str(&format!("{}{}\n", marker, markdown_inline(item, filter)));
+
+            // Anything under the item is indented to line up with its text
+            let mut nested = String::new();
+            for child in &item.inner_content {
+                to_markdown(child, filter, list_style, heading_level + 1, &mut nested);
+            }
+            markdown_lists(item, filter, list_style, heading_level + 1, &mut nested);
+            for child in item.children.iter().filter(|child| !child.is_empty_under(filter)) {
+                markdown_reply(child, filter, list_style, heading_level + 1, &mut nested);
+            }
+
+            for line in nested.lines().filter(|line| !line.is_empty()) {
+                result.push_str(&format!("{}{}\n", " ".repeat(marker.len()), line));
+            }
+        }
+        result.push('\n');
+    }
+}
+
+fn markdown_inline(content: &Content, filter: &ContentFilter) -> String {
+    let link = title_link(content, filter);
+
+    content
+        .sorted_values(filter)
+        .iter()
+        .filter(|value| !(value.meta.is_url && link.is_some()))
+        .map(|value| {
+            if value.meta.is_title {
+                match &link {
+                    Some(link) => format!("**[{}]({})**", escape_markdown(value.value.trim()), escape_markdown_url(link.value.trim())),
+                    None => format!("**{}**", escape_markdown(value.value.trim())),
+                }
+            } else {
+                markdown_value(value)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn markdown_value(value: &ContentValue) -> String {
+    let text = value.value.trim();
+
+    if value.meta.is_url && is_safe_link(text) {
+        format!("<{}>", escape_markdown_url(text))
+    } else {
+        escape_markdown(text)
+    }
+}
+
+fn to_html(
+    content: &Content,
+    filter: &ContentFilter,
+    list_style: &ListStyle,
+    heading_level: usize,
+    result: &mut String,
+) {
+    let values = content.sorted_values(filter);
+    let link = title_link(content, filter);
+    let level = heading_level.min(6);
+
+    for value in values.iter().filter(|value| value.meta.is_title) {
+        let text = escape_html(value.value.trim());
+
+        match &link {
+            Some(link) => result.push_str(&format!(
+                "<h{}><a href=\"{}\">{}</a></h{}>\n",
+                level, escape_html(link.value.trim()), text, level,
+            )),
+            None => result.push_str(&format!("<h{}>{}</h{}>\n", level, text, level)),
+        }
+    }
+
+    let body: Vec<String> = values
+        .iter()
+        .filter(|value| !value.meta.is_title)
+        .filter(|value| !(value.meta.is_url && link.is_some()))
+        .map(|value| {
+            let text = escape_html(value.value.trim());
+
+            if value.meta.is_url && is_safe_link(value.value.trim()) {
+                format!("<a href=\"{}\">{}</a>", text, text)
+            } else if value.meta.is_primary_content {
+                format!("<strong>{}</strong>", text)
+            } else {
+                format!("<span>{}</span>", text)
+            }
+        })
+        .collect();
+
+    if !body.is_empty() {
+        result.push_str(&format!("<p>{}</p>\n", body.join(" ")));
+    }
+
+    for child in &content.inner_content {
+        to_html(child, filter, list_style, heading_level + 1, result);
+    }
+
+    let list_tag = match list_style {
+        ListStyle::Bullet => "ul",
+        ListStyle::Numbered => "ol",
+    };
+
+    for list in &content.lists {
+        result.push_str(&format!("<{}>\n", list_tag));
+        for item in list.iter().filter(|item| !item.is_empty_under(filter)) {
+            result.push_str("<li>\n");
+            to_html(item, filter, list_style, heading_level + 1, result);
+            result.push_str("</li>\n");
+        }
+        result.push_str(&format!("</{}>\n", list_tag));
+    }
+
+    for child in content.children.iter().filter(|child| !child.is_empty_under(filter)) {
+        result.push_str("<blockquote>\n");
+        to_html(child, filter, list_style, heading_level + 1, result);
+        result.push_str("</blockquote>\n");
+    }
+}
+
+// The URL that belongs to a title on the same content, rendered as the title's link
+fn title_link(content: &Content, filter: &ContentFilter) -> Option<ContentValue> {
+    let values = content.sorted_values(filter);
+
+    if values.iter().any(|value| value.meta.is_title) {
+        values.into_iter().find(|value| value.meta.is_url && is_safe_link(value.value.trim()))
+    } else {
+        None
+    }
+}
+
+// Documents are often scraped, so only these schemes become live links.
+// Anything else, such as javascript:, is shown as text
+fn is_safe_link(url: &str) -> bool {
+    match url.split_once(':') {
+        Some((scheme, _)) => ["http", "https", "mailto"].contains(&scheme.to_ascii_lowercase().as_str()),
+        None => false,
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if "\\`*_[]<>#|".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+// Characters that would end a link destination or an autolink early
+fn escape_markdown_url(url: &str) -> String {
+    url.replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+        .replace('<', "%3C")
+        .replace('>', "%3E")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export_one(content: Content, format: ExportFormat) -> String {
+        export(&vec![content], &format, &ContentFilter::Everything, &ListStyle::Bullet)
+    }
+
+    fn post(id: &str, text: &str) -> Content {
+        Content::new(id).with_value(ContentValue::new("text", text))
+    }
+
+    #[test]
+    fn replies_under_list_items_are_quoted() {
+        let content = Content::new("root").with_list(vec![
+            post("one", "nested one"),
+            post("two", "nested two").with_child(post("reply", "reply para")),
+        ]);
+
+        let markdown = export_one(content, ExportFormat::Markdown);
+
+        assert!(markdown.contains("- nested two\n  > reply para\n"), "{}", markdown);
+    }
+
+    #[test]
+    fn only_web_and_mail_links_are_live() {
+        let content = Content::new("root")
+            .with_value(ContentValue::new("title", "Click me").title())
+            .with_value(ContentValue::new("url", "javascript:alert(1)").url())
+            .with_child(
+                Content::new("reply")
+                    .with_value(ContentValue::new("url", "JavaScript:alert(2)").url())
+                    .with_value(ContentValue::new("mail", "mailto:someone@example.com").url())
+            );
+
+        let html = export_one(content.clone(), ExportFormat::Html);
+        let markdown = export_one(content, ExportFormat::Markdown);
+
+        assert!(!html.contains("href=\"javascript"), "{}", html);
+        assert!(!html.contains("href=\"JavaScript"), "{}", html);
+        assert!(html.contains("<span>javascript:alert(1)</span>"), "{}", html);
+        assert!(html.contains("<a href=\"mailto:someone@example.com\">"), "{}", html);
+
+        assert!(markdown.contains("## Click me\n"), "{}", markdown);
+        assert!(!markdown.contains("<javascript"), "{}", markdown);
+        assert!(!markdown.contains("](javascript"), "{}", markdown);
+        assert!(markdown.contains("<mailto:someone@example.com>"), "{}", markdown);
+    }
+
+    #[test]
+    fn numbered_lists_use_ordered_markup() {
+        let content = Content::new("root").with_list(vec![post("one", "first"), post("two", "second")]);
+        let contents = vec![content];
+
+        let markdown = export(&contents, &ExportFormat::Markdown, &ContentFilter::Everything, &ListStyle::Numbered);
+        let html = export(&contents, &ExportFormat::Html, &ContentFilter::Everything, &ListStyle::Numbered);
+
+        assert!(markdown.contains("1. first\n2. second\n"), "{}", markdown);
+        assert!(html.contains("<ol>\n<li>"), "{}", html);
+    }
+}
@@ -0,0 +1,256 @@
+use crossterm::event::{Event};
+use ratatui::{backend::TestBackend, buffer::Buffer, Terminal};
+
+use crate::app::{App, AppOutcome};
+use crate::error::{Errors};
+use crate::input::{Input};
+use crate::keymap::{Key};
+use crate::options::{RenderOptions};
+use crate::session::{Session};
+
+#[derive(Clone, Debug)]
+pub struct Frame {
+    // The event that led to this frame, None for the first one
+    pub event: Option<Event>,
+    pub text: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct HeadlessRun {
+    pub session: Session,
+    pub outcome: Option<AppOutcome>,
+    pub frames: Vec<Frame>,
+}
+
+// Runs the app against an off-screen buffer, feeding it keys one at a time
+// and taking a snapshot of the screen after each. Keys left over once the app
+// has finished are ignored. Nothing reaches the outside: copying, opening
+// links and running commands only set the status line
+pub fn run(input: &Input, options: RenderOptions, width: u16, height: u16, keys: &[Key]) -> Result<HeadlessRun, Errors> {
+    let events: Vec<Event> = keys.iter().map(|key| Event::Key(key.to_event())).collect();
+
+    run_events(input, options, width, height, &events)
+}
+
+pub fn run_events(input: &Input, options: RenderOptions, width: u16, height: u16, events: &[Event]) -> Result<HeadlessRun, Errors> {
+    log::trace!("In headless::run_events");
+
+    let mut t = Terminal::new(TestBackend::new(width, height))
+        .map_err(|e| Errors::TerminalError(e.to_string()))?;
+
+    let mut app = App::with_input(input, options.side_effects(false));
+    let mut frames = Vec::new();
+    let mut outcome = None;
+
+    app.draw(&mut t).map_err(|e| Errors::TerminalError(e.to_string()))?;
+    frames.push(Frame {
+        event: None,
+        text: buffer_to_text(t.backend().buffer()),
+    });
+
+    for event in events {
+        if let Event::Resize(width, height) = event {
+            t.backend_mut().resize(*width, *height);
+        }
+
+        outcome = app.handle_event(event.clone());
+
+        app.draw(&mut t).map_err(|e| Errors::TerminalError(e.to_string()))?;
+        frames.push(Frame {
+            event: Some(event.clone()),
+            text: buffer_to_text(t.backend().buffer()),
+        });
+
+        if outcome.is_some() {
+            break;
+        }
+    }
+
+    Ok(HeadlessRun {
+        session: app.get_session(),
+        outcome: outcome,
+        frames: frames,
+    })
+}
+
+pub fn describe_event(event: &Event) -> String {
+    match event {
+        Event::Key(key) => Key::from(*key).to_string(),
+        Event::Resize(width, height) => format!("resize {}x{}", width, height),
+        other => format!("{:?}", other),
+    }
+}
+
+pub fn buffer_to_text(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut lines = Vec::new();
+
+    for y in area.top()..area.bottom() {
+        let line: String = (area.left()..area.right())
+            .map(|x| buffer.get(x, y).symbol())
+            .collect();
+
+        lines.push(line.trim_end().to_string());
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ListStyle};
+    use crate::input::{Content, ContentValue};
+
+    fn story(id: &str, title: &str, url: &str) -> Content {
+        Content::new(id)
+            .with_value(ContentValue::new("title", title).title())
+            .with_value(ContentValue::new("url", url).url())
+    }
+
+    fn document() -> Input {
+        Input::new(
+            Content::new("root").with_list(vec![
+                story("first", "First story", "https://example.com/1"),
+                story("second", "Second story", "https://example.com/2"),
+                story("third", "Third story", "https://example.com/3"),
+            ])
+        )
+    }
+
+    fn keys(text: &str) -> Vec<Key> {
+        Key::parse_sequence(text).unwrap()
+    }
+
+    #[test]
+    fn keys_select_and_return_a_link() {
+        let run = run(&document(), RenderOptions::default(), 60, 12, &keys("jj<Enter>")).unwrap();
+
+        assert_eq!(run.frames.len(), 4);
+        assert!(run.frames[0].event.is_none());
+        assert!(run.frames[0].text.contains("First story https://example.com/1"));
+        assert!(run.frames[1].text.contains(">First story"));
+        assert!(run.frames[2].text.contains(">Second story"));
+        assert!(!run.frames[2].text.contains(">First story"));
+        assert_eq!(describe_event(run.frames[3].event.as_ref().unwrap()), "<Enter>");
+
+        assert_eq!(run.session.value.as_deref(), Some("https://example.com/2"));
+        assert!(matches!(run.outcome, Some(AppOutcome::Selected(_))));
+    }
+
+    #[test]
+    fn keys_after_the_app_finishes_are_ignored() {
+        let run = run(&document(), RenderOptions::default(), 60, 12, &keys("jqjj")).unwrap();
+
+        assert_eq!(run.frames.len(), 3);
+        assert!(run.session.value.is_none());
+        assert!(matches!(run.outcome, Some(AppOutcome::Quit)));
+    }
+
+    #[test]
+    fn side_effects_only_set_the_status() {
+        let open = run(&document(), RenderOptions::default(), 80, 12, &keys("jo")).unwrap();
+        let copy = run(&document(), RenderOptions::default(), 80, 12, &keys("jyv")).unwrap();
+        let pipe = run(&document(), RenderOptions::default(), 80, 12, &keys("j:!cat<Enter>")).unwrap();
+
+        assert!(open.frames.last().unwrap().text.contains("Would open https://example.com/1"));
+        assert!(copy.frames.last().unwrap().text.contains("Would copy value"));
+        assert!(pipe.frames.last().unwrap().text.contains("Would run: cat"));
+        assert!(open.outcome.is_none());
+    }
+
+    #[test]
+    fn zooming_out_reselects_the_item_after_a_filter_change() {
+        let input = Input::new(
+            Content::new("root").with_list(vec![
+                Content::new("plain one").with_value(ContentValue::new("text", "plain one")),
+                Content::new("plain two").with_value(ContentValue::new("text", "plain two")),
+                story("third", "Third story", "https://example.com/3")
+                    .with_child(story("reply", "Reply", "https://example.com/reply")),
+                story("fourth", "Fourth story", "https://example.com/4"),
+            ])
+        );
+
+        let run = run(&input, RenderOptions::default(), 60, 12, &keys("jjjzppZ<Enter>")).unwrap();
+
+        assert_eq!(run.session.value.as_deref(), Some("https://example.com/3"));
+    }
+
+    #[test]
+    fn thread_parents_hidden_by_the_filter_keep_their_row() {
+        let input = Input::new(
+            Content::new("root").with_list(vec![
+                Content::new("first")
+                    .with_value(ContentValue::new("text", "untitled post"))
+                    .with_child(Content::new("reply").with_value(ContentValue::new("title", "Reply title").title())),
+                Content::new("second").with_value(ContentValue::new("title", "Second title").title()),
+            ])
+        );
+
+        let run = run(&input, RenderOptions::default(), 60, 16, &keys("tppjjv")).unwrap();
+        let screen = &run.frames[run.frames.len() - 2].text;
+
+        assert!(screen.contains("[hidden by filter]"));
+        assert!(screen.contains(">│ Reply title"));
+        assert_eq!(run.session.value.as_deref(), Some("Reply title"));
+    }
+
+    #[test]
+    fn numbered_lists_count_only_visible_items() {
+        let input = Input::new(
+            Content::new("root").with_list(vec![
+                Content::new("post").with_list(vec![
+                    Content::new("one").with_value(ContentValue::new("title", "one").title()),
+                    Content::new("two").with_value(ContentValue::new("text", "two")),
+                    Content::new("three").with_value(ContentValue::new("title", "three").title()),
+                ]),
+            ])
+        );
+        let options = RenderOptions::default().list_style(ListStyle::Numbered);
+
+        let run = run(&input, options, 60, 12, &keys("pp")).unwrap();
+        let screen = &run.frames.last().unwrap().text;
+
+        assert!(screen.contains("1. one"));
+        assert!(screen.contains("2. three"));
+        assert!(!screen.contains("3."));
+    }
+
+    #[test]
+    fn the_selection_option_selects_a_list_item() {
+        let options = RenderOptions::default().selection("third");
+
+        let run = run(&document(), options, 60, 12, &keys("<Enter>")).unwrap();
+
+        assert!(run.frames[0].text.contains(">Third story"));
+        assert_eq!(run.session.value.as_deref(), Some("https://example.com/3"));
+    }
+
+    #[test]
+    fn the_header_lists_every_ancestor_of_the_zoomed_item() {
+        let input = Input::new(
+            Content::new("root").with_list(vec![
+                story("alpha", "Alpha", "https://example.com/a").with_child(
+                    story("beta", "Beta", "https://example.com/b").with_child(
+                        story("gamma", "Gamma", "https://example.com/c")
+                            .with_child(story("delta", "Delta", "https://example.com/d"))
+                    )
+                ),
+            ])
+        );
+
+        let run = run(&input, RenderOptions::default(), 80, 16, &keys("tjjjz")).unwrap();
+
+        assert!(run.frames.last().unwrap().text.contains("document > Alpha > Beta > Gamma"));
+    }
+
+    #[test]
+    fn resizes_change_the_frame_size() {
+        let events = [Event::Resize(30, 5)];
+        let run = run_events(&document(), RenderOptions::default(), 60, 12, &events).unwrap();
+
+        assert_eq!(run.frames[0].text.split('\n').count(), 12);
+        assert_eq!(run.frames[1].text.split('\n').count(), 5);
+        assert_eq!(describe_event(run.frames[1].event.as_ref().unwrap()), "resize 30x5");
+    }
+}
@@ -1,19 +1,25 @@
 use serde::{Serialize, Deserialize};
-use std::collections::{HashMap};
+use schemars::{schema_for, JsonSchema};
+use std::collections::{HashMap, HashSet};
 use itertools::Itertools;
 use std::cmp::Ordering;
 use ratatui::{prelude::*, widgets::*};
 use textwrap;
 use std::str::FromStr;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+use crate::config::{ListStyle};
+use crate::table::{TableSort, list_schema, table_to_lines};
+
+pub const DEFAULT_LINE_WIDTH: usize = 160;
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default)]
 pub struct ContentValueMetadata {
     pub is_title: bool,
     pub is_primary_content: bool,
     pub is_url: bool,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct ContentValue {
     pub meta: ContentValueMetadata,
     pub name: String,
@@ -26,7 +32,7 @@ pub struct ContentMetadataRecursive {
     pub parent_id: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Content {
     pub id: String,
     #[serde(default)]
@@ -39,13 +45,164 @@ pub struct Content {
     pub lists: Vec<Vec<Content>>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContentFilter {
+    Everything,
+    PrimaryOnly,
+    TitlesOnly,
+    LinksOnly,
+}
+
+pub struct LineOptions<'a> {
+    pub filter: ContentFilter,
+    pub main_content_color: Color,
+    pub text_color: Color,
+    pub background_color: Color,
+    pub list_style: ListStyle,
+    pub folded_lists: &'a HashSet<String>,
+    pub table_sorts: &'a HashMap<String, TableSort>,
+    pub highlighted_value: Option<usize>,
+    pub highlight_color: Color,
+    pub marked_values: Vec<ValuePath>,
+    pub mark_color: Color,
+    pub width: usize,
+}
+
+// Where a value lives in the document: the content holding it and its index
+// among that content's values. Unlike its place on screen, this stays the same
+// when the filter, depth or layout changes
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ValuePath {
+    pub content_id: String,
+    pub index: usize,
+}
+
+// Rendered lines along with the values shown on each of them, in display order.
+// Values are numbered across the whole rendering so a cursor can address them
+pub struct RenderedLines {
+    pub lines: Vec<Line<'static>>,
+    pub values: Vec<Vec<ContentValue>>,
+    pub paths: Vec<Vec<ValuePath>>,
+    value_offset: usize,
+}
+
+impl ContentFilter {
+    pub fn includes(&self, value: &ContentValue) -> bool {
+        match self {
+            ContentFilter::Everything => true,
+            ContentFilter::PrimaryOnly => value.meta.is_primary_content,
+            ContentFilter::TitlesOnly => value.meta.is_title,
+            ContentFilter::LinksOnly => value.meta.is_url,
+        }
+    }
+
+    pub fn next(&self) -> ContentFilter {
+        match self {
+            ContentFilter::Everything => ContentFilter::PrimaryOnly,
+            ContentFilter::PrimaryOnly => ContentFilter::TitlesOnly,
+            ContentFilter::TitlesOnly => ContentFilter::LinksOnly,
+            ContentFilter::LinksOnly => ContentFilter::Everything,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ContentFilter::Everything => "everything",
+            ContentFilter::PrimaryOnly => "primary content",
+            ContentFilter::TitlesOnly => "titles",
+            ContentFilter::LinksOnly => "links",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Input {
     pub content: Content,
     pub related_content: Content,
 }
 
+// Field names as they appear in the document, checked by validation
+pub const INPUT_FIELDS: [&str; 2] = ["content", "related_content"];
+pub const CONTENT_FIELDS: [&str; 5] = ["id", "values", "inner_content", "children", "lists"];
+pub const CONTENT_VALUE_FIELDS: [&str; 3] = ["meta", "name", "value"];
+pub const METADATA_FIELDS: [&str; 3] = ["is_title", "is_primary_content", "is_url"];
+
+impl ContentValue {
+    pub fn new(name: &str, value: &str) -> ContentValue {
+        ContentValue {
+            meta: ContentValueMetadata::default(),
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    pub fn title(mut self) -> ContentValue {
+        self.meta.is_title = true;
+        self
+    }
+
+    pub fn primary(mut self) -> ContentValue {
+        self.meta.is_primary_content = true;
+        self
+    }
+
+    pub fn url(mut self) -> ContentValue {
+        self.meta.is_url = true;
+        self
+    }
+}
+
+impl Input {
+    pub fn new(content: Content) -> Input {
+        Input {
+            content: content,
+            related_content: Content::new("related_content"),
+        }
+    }
+
+    pub fn with_related_content(mut self, related_content: Content) -> Input {
+        self.related_content = related_content;
+        self
+    }
+
+    // Generated from the types themselves, so it accepts exactly what
+    // deserialization does
+    pub fn json_schema() -> serde_json::Value {
+        serde_json::to_value(schema_for!(Input)).unwrap()
+    }
+}
+
 impl Content {
+    pub fn new(id: &str) -> Content {
+        Content {
+            id: id.to_string(),
+            values: Vec::new(),
+            inner_content: Vec::new(),
+            children: Vec::new(),
+            lists: Vec::new(),
+        }
+    }
+
+    pub fn with_value(mut self, value: ContentValue) -> Content {
+        self.values.push(value);
+        self
+    }
+
+    pub fn with_inner_content(mut self, content: Content) -> Content {
+        self.inner_content.push(content);
+        self
+    }
+
+    pub fn with_child(mut self, child: Content) -> Content {
+        self.children.push(child);
+        self
+    }
+
+    pub fn with_list(mut self, list: Vec<Content>) -> Content {
+        self.lists.push(list);
+        self
+    }
+
     pub fn go_down_depth(&self, depth: usize, results: &mut Vec<Content>) {
         if depth == 0 {
             results.push(self.clone());
@@ -66,39 +223,169 @@ impl Content {
         }
     }
 
-    pub fn to_lines(
-        &self,
-        filter_secondary_content: &bool,
-        main_content_color: &Color,
-        text_color: &Color,
-        background_color: &Color,
-        result: &mut Vec<Line>,
-        indent_size: usize,
-    ) {
-        let values: Vec<ContentValue> = self.values.iter()
+    pub fn inner_depth(&self) -> usize {
+        self.inner_content
+            .iter()
+            .map(|child| child.inner_depth() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn count_replies(&self) -> usize {
+        self.children
+            .iter()
+            .map(|child| child.count_replies() + 1)
+            .sum()
+    }
+
+    pub fn list_key(&self, list_index: usize) -> String {
+        format!("{}:{}", self.id, list_index)
+    }
+
+    pub fn list_keys(&self, result: &mut Vec<String>) {
+        for (index, list) in self.lists.iter().enumerate() {
+            result.push(self.list_key(index));
+
+            for item in list {
+                item.list_keys(result);
+            }
+        }
+
+        for child in self.inner_content.iter().chain(self.children.iter()) {
+            child.list_keys(result);
+        }
+    }
+
+    pub fn is_empty_under(&self, filter: &ContentFilter) -> bool {
+        !self.values.iter().any(|value| filter.includes(value)) &&
+        self.inner_content.iter().all(|child| child.is_empty_under(filter)) &&
+        self.children.iter().all(|child| child.is_empty_under(filter)) &&
+        self.lists.iter().flatten().all(|item| item.is_empty_under(filter))
+    }
+
+    // The link an item leads to: a URL next to a title wins over any other URL
+    pub fn get_default_link(&self) -> Option<ContentValue> {
+        self.find_titled_link().or_else(|| self.find_first_link())
+    }
+
+    fn find_titled_link(&self) -> Option<ContentValue> {
+        if self.values.iter().any(|value| value.meta.is_title) {
+            if let Some(link) = self.values.iter().find(|value| value.meta.is_url) {
+                return Some(link.clone());
+            }
+        }
+
+        self.nested_content().find_map(|child| child.find_titled_link())
+    }
+
+    fn find_first_link(&self) -> Option<ContentValue> {
+        if let Some(link) = self.values.iter().find(|value| value.meta.is_url) {
+            return Some(link.clone());
+        }
+
+        self.nested_content().find_map(|child| child.find_first_link())
+    }
+
+    fn nested_content(&self) -> impl Iterator<Item = &Content> {
+        self.inner_content
+            .iter()
+            .chain(self.children.iter())
+            .chain(self.lists.iter().flatten())
+    }
+
+    pub fn matches_query(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+
+        self.values.iter().any(|value| value.value.to_lowercase().contains(&query)) ||
+        self.nested_content().any(|child| child.matches_query(&query))
+    }
+
+    pub fn has_nested_content(&self) -> bool {
+        !self.inner_content.is_empty() || !self.children.is_empty() || !self.lists.is_empty()
+    }
+
+    pub fn contains_id(&self, id: &str) -> bool {
+        self.inner_content.iter().any(|child| child.id == id || child.contains_id(id)) ||
+        self.children.iter().any(|child| child.id == id || child.contains_id(id)) ||
+        self.lists.iter().flatten().any(|item| item.id == id || item.contains_id(id))
+    }
+
+    // The contents from this one down to the one with this id, both included
+    pub fn path_to(&self, id: &str) -> Option<Vec<&Content>> {
+        if self.id == id {
+            return Some(vec![self]);
+        }
+
+        let mut path = self.nested_content().find_map(|child| child.path_to(id))?;
+        path.insert(0, self);
+        Some(path)
+    }
+
+    pub fn get_title(&self) -> Option<String> {
+        let value = self.values
+            .iter()
+            .find(|value| value.meta.is_title)
+            .or(self.values.first())?;
+
+        let title: String = value.value.trim().chars().take(40).collect();
+
+        if title.is_empty() {
+            None
+        } else {
+            Some(title)
+        }
+    }
+
+    // Values in display order: primary content first, then by name
+    pub fn sorted_values(&self, filter: &ContentFilter) -> Vec<ContentValue> {
+        self.sorted_values_with_paths(filter)
             .into_iter()
-            .sorted_by(|a, b| {
+            .map(|(_, value)| value)
+            .collect()
+    }
+
+    pub fn sorted_values_with_paths(&self, filter: &ContentFilter) -> Vec<(ValuePath, ContentValue)> {
+        self.values.iter()
+            .enumerate()
+            .filter(|(_, value)| filter.includes(value))
+            .sorted_by(|(_, a), (_, b)| {
                 match (a.meta.is_primary_content, b.meta.is_primary_content) {
                     (true, false) => std::cmp::Ordering::Less,
                     (false, true) => std::cmp::Ordering::Greater,
                     _ => a.name.cmp(&b.name)
                 }
             })
-            .cloned()
-            .collect();
+            .map(|(index, value)| (self.value_path(index), value.clone()))
+            .collect()
+    }
+
+    pub fn value_path(&self, index: usize) -> ValuePath {
+        ValuePath {
+            content_id: self.id.clone(),
+            index: index,
+        }
+    }
+
+    pub fn to_lines(
+        &self,
+        options: &LineOptions,
+        result: &mut RenderedLines,
+        indent_size: usize,
+    ) {
+        let values: Vec<(ValuePath, ContentValue)> = self.sorted_values_with_paths(&options.filter);
 
-        let mut lines: Vec<Line> = Vec::new();
         let mut current_line: Line = Line::from(Vec::new());
+        let mut current_values: Vec<(ValuePath, ContentValue)> = Vec::new();
 
         let indent = " ".repeat(indent_size * 2);
 
-        for (index, item) in values.iter().enumerate() {
-            let mut value = item.value.trim();
+        for (path, item) in values.iter() {
+            let value = item.value.trim();
 
             let mut fg = if item.meta.is_primary_content {
-                *main_content_color
+                options.main_content_color
             } else {
-                *text_color
+                options.text_color
             };
 
             let current_line_length: usize = current_line.spans
@@ -109,7 +396,14 @@ impl Content {
                 fg = Color::from_str("#0000FF").unwrap();
             }
 
-            let mut bg = *background_color;
+            let mut bg = options.background_color;
+
+            let ordinal = result.value_count() + current_values.len();
+            if options.highlighted_value == Some(ordinal) {
+                bg = options.highlight_color;
+            } else if options.marked_values.contains(path) {
+                bg = options.mark_color;
+            }
 
             let mut style = Style::new().fg(fg).bg(bg);
 
@@ -125,28 +419,37 @@ impl Content {
                 format!("{}", indent),
             );
 
-            if value.len() > 160 {
+            if value.len() > options.width {
                 if current_line_length > 0 {
-                    lines.push(current_line);
+                    result.push(current_line, current_values);
                     current_line = Line::from(Vec::new());
+                    current_values = Vec::new();
                 }
 
-                let wrapped = textwrap::wrap(value, &textwrap::Options::new(160));
+                let wrapped = textwrap::wrap(value, &textwrap::Options::new(options.width));
+
+                for (segment_index, segment) in wrapped.iter().enumerate() {
+                    // A wrapped value is a single cursor stop on its first line
+                    let segment_values = if segment_index == 0 {
+                        vec![(path.clone(), item.clone())]
+                    } else {
+                        Vec::new()
+                    };
 
-                for segment in wrapped {
-                    lines.push(
+                    result.push(
                         Line::from(vec![
                             indent_span.clone(),
                             Span::styled(
                                 format!("{}", segment),
                                 style,
                             )
-                        ])
+                        ]),
+                        segment_values,
                     );
                 }
             } else {
-                if value.len() + current_line_length > 160 {
-                    lines.push(current_line);
+                if value.len() + current_line_length > options.width {
+                    result.push(current_line, current_values);
                     current_line = Line::from(vec![
                         indent_span.clone(),
                         Span::styled(
@@ -154,6 +457,7 @@ impl Content {
                             style,
                         )
                     ]);
+                    current_values = vec![(path.clone(), item.clone())];
                 } else {
                     current_line.spans.push(indent_span.clone());
                     current_line.spans.push(
@@ -165,6 +469,7 @@ impl Content {
                     current_line.spans.push(
                         Span::raw(format!("{}", " ".to_string()))
                     );
+                    current_values.push((path.clone(), item.clone()));
                 }
             }
         }
@@ -174,47 +479,190 @@ impl Content {
             .map(|span| span.content.len()).sum();
 
         if current_line_length > 0 {
-            lines.push(current_line);
+            result.push(current_line, current_values);
         }
 
-        result.append(&mut lines);
-
         for child in &self.inner_content {
             child.to_lines(
-                filter_secondary_content,
-                main_content_color,
-                text_color,
-                background_color,
+                options,
                 result, 
                 indent_size + 1,
             );
         }
 
         for child in &self.children {
-            result.push(Line::from("".to_string()));
+            if child.is_empty_under(&options.filter) {
+                continue;
+            }
+
+            result.push(Line::from("".to_string()), Vec::new());
             child.to_lines(
-                filter_secondary_content,
-                main_content_color,
-                text_color,
-                background_color,
+                options,
                 result,
                 indent_size + 2,
             );
         }
 
-        for list in &self.lists {
-            result.push(Line::from("---".to_string()));
-            for item in list {
-                result.push(Line::from("-".to_string()));
-                item.to_lines(
-                    filter_secondary_content,
-                    main_content_color,
-                    text_color,
-                    background_color,
-                    result, 
-                    indent_size + 1,
-                );
+        for (list_index, list) in self.lists.iter().enumerate() {
+            if list.iter().all(|item| item.is_empty_under(&options.filter)) {
+                continue;
             }
+
+            self.list_to_lines(
+                list,
+                &self.list_key(list_index),
+                options,
+                result,
+                indent_size + 1,
+            );
         }
     }
+
+    fn list_to_lines(
+        &self,
+        list: &Vec<Content>,
+        list_key: &str,
+        options: &LineOptions,
+        result: &mut RenderedLines,
+        indent_size: usize,
+    ) {
+        let indent = " ".repeat(indent_size * 2);
+        let marker_style = Style::new().fg(options.text_color).bg(options.background_color);
+
+        if options.folded_lists.contains(list_key) {
+            let noun = if list.len() == 1 { "item" } else { "items" };
+
+            result.push(
+                Line::from(vec![
+                    Span::raw(indent.clone()),
+                    Span::styled(
+                        format!("▸ {} {}", list.len(), noun),
+                        marker_style.add_modifier(Modifier::ITALIC),
+                    ),
+                ]),
+                Vec::new(),
+            );
+
+            return;
+        }
+
+        if let Some(columns) = list_schema(list) {
+            table_to_lines(
+                list,
+                &columns,
+                options.table_sorts.get(list_key),
+                options,
+                result,
+                indent_size,
+            );
+
+            return;
+        }
+
+        // Numbers count only the items the filter leaves, as the export does
+        let visible: Vec<&Content> = list
+            .iter()
+            .filter(|item| !item.is_empty_under(&options.filter))
+            .collect();
+
+        let marker_width = match options.list_style {
+            ListStyle::Bullet => 2,
+            ListStyle::Numbered => format!("{}. ", visible.len()).len(),
+        };
+
+        for (index, item) in visible.into_iter().enumerate() {
+            let marker = match options.list_style {
+                ListStyle::Bullet => "• ".to_string(),
+                ListStyle::Numbered => format!("{:<width$}", format!("{}.", index + 1), width = marker_width),
+            };
+
+            let mut item_lines = RenderedLines::starting_after(result);
+            item.to_lines(options, &mut item_lines, 0);
+
+            for (line_index, (mut line, values)) in item_lines.into_iter().enumerate() {
+                let prefix = if line_index == 0 {
+                    Span::styled(marker.clone(), marker_style)
+                } else {
+                    Span::raw(" ".repeat(marker_width))
+                };
+
+                line.spans.insert(0, prefix);
+                line.spans.insert(0, Span::raw(indent.clone()));
+                result.push(line, values);
+            }
+        }
+    }
+}
+
+impl RenderedLines {
+    pub fn new() -> Self {
+        RenderedLines {
+            lines: Vec::new(),
+            values: Vec::new(),
+            paths: Vec::new(),
+            value_offset: 0,
+        }
+    }
+
+    // Continues the value numbering of another set of lines, so nested
+    // content rendered separately can be appended without renumbering
+    pub fn starting_after(other: &RenderedLines) -> Self {
+        RenderedLines {
+            lines: Vec::new(),
+            values: Vec::new(),
+            paths: Vec::new(),
+            value_offset: other.value_count(),
+        }
+    }
+
+    pub fn push(&mut self, line: Line<'static>, values: Vec<(ValuePath, ContentValue)>) {
+        let (paths, values): (Vec<ValuePath>, Vec<ContentValue>) = values.into_iter().unzip();
+
+        self.lines.push(line);
+        self.values.push(values);
+        self.paths.push(paths);
+    }
+
+    pub fn value_count(&self) -> usize {
+        self.value_offset + self.values.iter().map(|values| values.len()).sum::<usize>()
+    }
+
+    pub fn value_grid(&self) -> Vec<Vec<ContentValue>> {
+        self.values
+            .iter()
+            .filter(|values| !values.is_empty())
+            .cloned()
+            .collect()
+    }
+
+    // The paths of the values in value_grid, in the same shape
+    pub fn path_grid(&self) -> Vec<Vec<ValuePath>> {
+        self.paths
+            .iter()
+            .filter(|paths| !paths.is_empty())
+            .cloned()
+            .collect()
+    }
+
+    pub fn to_text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn into_iter(self) -> impl Iterator<Item = (Line<'static>, Vec<(ValuePath, ContentValue)>)> {
+        self.lines
+            .into_iter()
+            .zip(self.paths.into_iter().zip(self.values.into_iter()))
+            .map(|(line, (paths, values))| (line, paths.into_iter().zip(values.into_iter()).collect()))
+    }
 }
@@ -0,0 +1,396 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    First,
+    Last,
+    Down,
+    Up,
+    Left,
+    Right,
+    NextValueLine,
+    PreviousValueLine,
+    CycleFilter,
+    Higher,
+    Deeper,
+    ZoomIn,
+    ZoomOut,
+    ToggleThreadView,
+    ToggleCardView,
+    ToggleCollapse,
+    ToggleFoldLists,
+    CycleTableSort,
+    ReverseTableSort,
+    JumpToParent,
+    NextSibling,
+    NextThread,
+    ExitWithValue,
+    ToggleMark,
+    Yank,
+    OpenLink,
+    CommandLine,
+    ShellCommand,
+    ToggleVisual,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    MarkAllMatches,
+    Cancel,
+    Confirm,
+    Back,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: HashMap<Key, Action>,
+}
+
+const DEFAULT_BINDINGS: [(&str, Action); 37] = [
+    ("q", Action::Quit),
+    ("g", Action::First),
+    ("G", Action::Last),
+    ("j", Action::Down),
+    ("k", Action::Up),
+    ("h", Action::Left),
+    ("l", Action::Right),
+    ("J", Action::NextValueLine),
+    ("K", Action::PreviousValueLine),
+    ("p", Action::CycleFilter),
+    ("-", Action::Higher),
+    ("+", Action::Deeper),
+    ("z", Action::ZoomIn),
+    ("Z", Action::ZoomOut),
+    ("t", Action::ToggleThreadView),
+    ("C", Action::ToggleCardView),
+    ("c", Action::ToggleCollapse),
+    ("f", Action::ToggleFoldLists),
+    ("s", Action::CycleTableSort),
+    ("S", Action::ReverseTableSort),
+    ("u", Action::JumpToParent),
+    ("]", Action::NextSibling),
+    ("}", Action::NextThread),
+    ("v", Action::ExitWithValue),
+    ("<Space>", Action::ToggleMark),
+    ("y", Action::Yank),
+    ("o", Action::OpenLink),
+    (":", Action::CommandLine),
+    ("!", Action::ShellCommand),
+    ("V", Action::ToggleVisual),
+    ("/", Action::Search),
+    ("n", Action::NextMatch),
+    ("N", Action::PreviousMatch),
+    ("*", Action::MarkAllMatches),
+    ("<Esc>", Action::Cancel),
+    ("<Enter>", Action::Confirm),
+    ("<BS>", Action::Back),
+];
+
+impl Key {
+    // Single characters stand for themselves, everything else is written in
+    // angle brackets, such as <Enter>, <Esc>, <Space>, <BS>, <Tab>, <Up> or
+    // <C-x>. Modifiers are C- for control, A- or M- for alt, S- for shift and
+    // D- for super
+    pub fn parse(text: &str) -> Option<Key> {
+        let mut chars = text.chars();
+
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Some(Key::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+
+        let name = text.strip_prefix('<')?.strip_suffix('>')?;
+
+        for (prefix, modifier) in MODIFIER_PREFIXES {
+            let rest = match name.get(..prefix.len()).zip(name.get(prefix.len()..)) {
+                Some((start, rest)) if start.eq_ignore_ascii_case(prefix) && !rest.is_empty() => rest,
+                _ => continue,
+            };
+
+            let key = Key::parse(rest).or_else(|| Key::parse(&format!("<{}>", rest)))?;
+            return Some(Key::new(key.code, key.modifiers | modifier));
+        }
+
+        let code = match name.to_lowercase().as_str() {
+            "enter" | "cr" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "space" => KeyCode::Char(' '),
+            "bs" | "backspace" => KeyCode::Backspace,
+            "tab" => KeyCode::Tab,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "del" => KeyCode::Delete,
+            "lt" => KeyCode::Char('<'),
+            "gt" => KeyCode::Char('>'),
+            other => match other.strip_prefix('f').and_then(|number| number.parse::<u8>().ok()) {
+                Some(number) => KeyCode::F(number),
+                None => return None,
+            },
+        };
+
+        Some(Key::new(code, KeyModifiers::NONE))
+    }
+
+    // A run of keys in the same notation, such as jj<C-d><Enter>. A lone < is
+    // the key itself
+    pub fn parse_sequence(text: &str) -> Result<Vec<Key>, String> {
+        let mut keys = Vec::new();
+        let mut rest = text;
+
+        while let Some(c) = rest.chars().next() {
+            let name_end = match c {
+                '<' => rest.find('>').filter(|end| {
+                    let name = &rest[1..*end];
+                    !name.is_empty() && !name.contains(|c: char| c == '<' || c.is_whitespace())
+                }),
+                _ => None,
+            };
+
+            let (key, length) = match name_end {
+                Some(end) => {
+                    let token = &rest[..=end];
+                    (Key::parse(token).ok_or_else(|| format!("unknown key: {}", token))?, end + 1)
+                }
+                None => (Key::new(KeyCode::Char(c), KeyModifiers::NONE), c.len_utf8()),
+            };
+
+            keys.push(key);
+            rest = &rest[length..];
+        }
+
+        Ok(keys)
+    }
+
+    pub fn to_event(&self) -> KeyEvent {
+        KeyEvent::new(self.code, self.modifiers)
+    }
+
+    // Terminals report shift along with the shifted character, the character
+    // alone identifies the key
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        let modifiers = match code {
+            KeyCode::Char(_) => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+
+        Key {
+            code: code,
+            modifiers: modifiers,
+        }
+    }
+}
+
+// The order keys are printed with, M- is read as another name for alt
+const MODIFIER_PREFIXES: [(&str, KeyModifiers); 5] = [
+    ("C-", KeyModifiers::CONTROL),
+    ("A-", KeyModifiers::ALT),
+    ("M-", KeyModifiers::ALT),
+    ("S-", KeyModifiers::SHIFT),
+    ("D-", KeyModifiers::SUPER),
+];
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char('<') => "lt".to_string(),
+            KeyCode::Char('>') => "gt".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Backspace => "BS".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::Up => "Up".to_string(),
+            KeyCode::Down => "Down".to_string(),
+            KeyCode::Left => "Left".to_string(),
+            KeyCode::Right => "Right".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PageUp".to_string(),
+            KeyCode::PageDown => "PageDown".to_string(),
+            KeyCode::Delete => "Del".to_string(),
+            KeyCode::F(number) => format!("F{}", number),
+            other => format!("{:?}", other),
+        };
+
+        let prefixes: String = MODIFIER_PREFIXES
+            .iter()
+            .filter(|(prefix, modifier)| *prefix != "M-" && self.modifiers.contains(*modifier))
+            .map(|(prefix, _)| *prefix)
+            .collect();
+
+        match (name.chars().count(), prefixes.is_empty()) {
+            (1, true) => write!(f, "{}", name),
+            _ => write!(f, "<{}{}>", prefixes, name),
+        }
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Key {
+        Key::new(event.code, event.modifiers)
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Keymap::empty();
+
+        for (key, action) in DEFAULT_BINDINGS {
+            keymap.bind(key, action).unwrap();
+        }
+
+        keymap
+    }
+}
+
+// Written as a map from each key, in the notation Key::parse reads, to its action
+impl Serialize for Keymap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bindings: BTreeMap<String, Action> = self.bindings
+            .iter()
+            .map(|(key, action)| (key.to_string(), *action))
+            .collect();
+
+        bindings.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Keymap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Keymap, D::Error> {
+        let bindings = BTreeMap::<String, Action>::deserialize(deserializer)?;
+        let mut keymap = Keymap::empty();
+
+        for (key, action) in bindings {
+            keymap.bind(&key, action).map_err(serde::de::Error::custom)?;
+        }
+
+        Ok(keymap)
+    }
+}
+
+impl Keymap {
+    pub fn empty() -> Keymap {
+        Keymap {
+            bindings: HashMap::new(),
+        }
+    }
+
+    pub fn bind(&mut self, key: &str, action: Action) -> Result<(), String> {
+        let key = Key::parse(key).ok_or_else(|| format!("unknown key: {}", key))?;
+        self.bindings.insert(key, action);
+        Ok(())
+    }
+
+    pub fn unbind(&mut self, key: &str) -> Result<(), String> {
+        let key = Key::parse(key).ok_or_else(|| format!("unknown key: {}", key))?;
+        self.bindings.remove(&key);
+        Ok(())
+    }
+
+    pub fn action_for(&self, key: &Key) -> Option<Action> {
+        self.bindings.get(key).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> Key {
+        Key::new(code, KeyModifiers::NONE)
+    }
+
+    fn chars(text: &str) -> Vec<Key> {
+        text.chars().map(|c| key(KeyCode::Char(c))).collect()
+    }
+
+    #[test]
+    fn parses_plain_and_named_keys() {
+        assert_eq!(
+            Key::parse_sequence("jj<Enter>").unwrap(),
+            vec![key(KeyCode::Char('j')), key(KeyCode::Char('j')), key(KeyCode::Enter)]
+        );
+        assert_eq!(
+            Key::parse_sequence("<Space><esc><BS><F5>").unwrap(),
+            vec![key(KeyCode::Char(' ')), key(KeyCode::Esc), key(KeyCode::Backspace), key(KeyCode::F(5))]
+        );
+        assert_eq!(
+            Key::parse_sequence("<C-d><c-Enter>").unwrap(),
+            vec![
+                Key::new(KeyCode::Char('d'), KeyModifiers::CONTROL),
+                Key::new(KeyCode::Enter, KeyModifiers::CONTROL),
+            ]
+        );
+        assert_eq!(Key::parse_sequence("").unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn angle_brackets_that_do_not_name_a_key_are_literal() {
+        assert_eq!(Key::parse_sequence("<").unwrap(), chars("<"));
+        assert_eq!(Key::parse_sequence("<>").unwrap(), chars("<>"));
+        assert_eq!(Key::parse_sequence("a<b").unwrap(), chars("a<b"));
+        assert_eq!(Key::parse_sequence("<Enter").unwrap(), chars("<Enter"));
+        assert_eq!(Key::parse_sequence("< x>").unwrap(), chars("< x>"));
+        assert_eq!(Key::parse_sequence("<<Enter>").unwrap(), vec![key(KeyCode::Char('<')), key(KeyCode::Enter)]);
+        assert_eq!(Key::parse_sequence("<lt><gt>").unwrap(), chars("<>"));
+        assert_eq!(Key::parse_sequence("é").unwrap(), chars("é"));
+    }
+
+    #[test]
+    fn unknown_key_names_are_errors() {
+        assert_eq!(Key::parse_sequence("j<Nope>"), Err("unknown key: <Nope>".to_string()));
+        assert_eq!(Key::parse_sequence("<C-Nope>"), Err("unknown key: <C-Nope>".to_string()));
+        assert_eq!(Key::parse_sequence("<F999>"), Err("unknown key: <F999>".to_string()));
+    }
+
+    #[test]
+    fn keys_print_in_the_notation_they_parse_from() {
+        for (text, _) in DEFAULT_BINDINGS {
+            let key = Key::parse(text).unwrap();
+            assert_eq!(Key::parse(&key.to_string()), Some(key), "{}", text);
+        }
+
+        let control = Key::new(KeyCode::Char('x'), KeyModifiers::CONTROL);
+        assert_eq!(control.to_string(), "<C-x>");
+        assert_eq!(Key::parse("<C-x>"), Some(control));
+
+        let alt = Key::new(KeyCode::Char('j'), KeyModifiers::ALT);
+        assert_eq!(alt.to_string(), "<A-j>");
+        assert_eq!(Key::parse("<M-j>"), Some(alt));
+        assert_ne!(Key::parse(&alt.to_string()), Key::parse("j"));
+
+        let everything = Key::new(KeyCode::Tab, KeyModifiers::all() - KeyModifiers::HYPER - KeyModifiers::META);
+        assert_eq!(everything.to_string(), "<C-A-S-D-Tab>");
+        assert_eq!(Key::parse(&everything.to_string()), Some(everything));
+    }
+
+    #[test]
+    fn keymaps_round_trip_through_json() {
+        let mut keymap = Keymap::default();
+        keymap.bind("<C-n>", Action::Down).unwrap();
+        keymap.unbind("j").unwrap();
+
+        let json = serde_json::to_string(&keymap).unwrap();
+        let loaded: Keymap = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.action_for(&Key::parse("<C-n>").unwrap()), Some(Action::Down));
+        assert_eq!(loaded.action_for(&Key::parse("j").unwrap()), None);
+        assert_eq!(loaded.action_for(&Key::parse("k").unwrap()), Some(Action::Up));
+    }
+}
@@ -1,30 +1,138 @@
-mod error;
 mod terminal;
-mod input;
-mod session;
-mod app;
+mod analysis;
+mod table;
+mod clipboard;
+mod command;
+mod shell;
 
+pub mod app;
+pub mod error;
+pub mod input;
+pub mod keymap;
+pub mod options;
 pub mod history;
+pub mod session;
+pub mod config;
+pub mod opener;
+pub mod export;
+pub mod dump;
+pub mod validate;
+pub mod headless;
+pub mod recording;
 
 use error::{Errors};
 use terminal::{start_interface};
 use input::{Input};
 use session::{Session};
 use history::{History};
+use config::{Config};
+use options::{RenderOptions};
+use keymap::{Key};
+use headless::{HeadlessRun};
+use export::{ExportFormat, ExportScope};
+use analysis::{resolve_depth};
+use dump::{DumpOptions};
 
 pub fn render(json: String, history: Option<History>) -> Result<Session, Errors> {
-    log::trace!("In render");
+    render_with_config(json, history, Config::default())
+}
+
+pub fn render_with_config(json: String, history: Option<History>, config: Config) -> Result<Session, Errors> {
+    log::trace!("In render_with_config");
     log::trace!("json: {}", json);
 
-    let input: Input = serde_json::from_str(&json).map_err(|e| {
-        log::error!("deserialization error: {}", e);
-        Errors::DeserializationError
-    })?;
+    let input = parse_input(&json)?;
 
     log::info!("Successfully deserialized JSON");
 
-    start_interface(&input, &history).map_err(|e| {
+    let mut options = RenderOptions::from_config(config);
+    options.history = history;
+
+    render_input(&input, options)
+}
+
+pub fn render_input(input: &Input, options: RenderOptions) -> Result<Session, Errors> {
+    log::trace!("In render_input");
+
+    start_interface(input, options).map_err(|e| {
         log::error!("{}", e);
-        Errors::UnexpectedError
+        Errors::TerminalError(e.to_string())
+    })
+}
+
+pub fn export_document(json: String, config: Config, format: ExportFormat, scope: ExportScope) -> Result<String, Errors> {
+    log::trace!("In export_document");
+
+    let input = parse_input(&json)?;
+
+    let contents = match scope {
+        ExportScope::Document => vec![input.content],
+        ExportScope::Depth => {
+            let mut results = Vec::new();
+            input.content.go_down_depth(resolve_depth(&config.initial_depth, &input.content), &mut results);
+            results
+        }
+    };
+
+    Ok(export::export(&contents, &format, &input::ContentFilter::Everything, &config.list_style))
+}
+
+pub fn dump_document(json: String, config: Config, options: DumpOptions) -> Result<String, Errors> {
+    log::trace!("In dump_document");
+
+    let input = parse_input(&json)?;
+
+    let mut contents = Vec::new();
+    input.content.go_down_depth(resolve_depth(&config.initial_depth, &input.content), &mut contents);
+
+    Ok(dump::dump(
+        &contents,
+        &options,
+        &input::ContentFilter::Everything,
+        &config.list_style,
+        &config.theme.secondary_hex,
+    ))
+}
+
+pub fn render_headless(
+    input: &Input,
+    options: RenderOptions,
+    width: u16,
+    height: u16,
+    keys: &[Key]
+) -> Result<HeadlessRun, Errors> {
+    headless::run(input, options, width, height, keys)
+}
+
+pub fn render_headless_events(
+    input: &Input,
+    options: RenderOptions,
+    width: u16,
+    height: u16,
+    events: &[crossterm::event::Event]
+) -> Result<HeadlessRun, Errors> {
+    headless::run_events(input, options, width, height, events)
+}
+
+pub fn parse_input(json: &str) -> Result<Input, Errors> {
+    if json.trim().is_empty() {
+        return Err(Errors::InvalidInput("the document is empty".to_string()));
+    }
+
+    serde_json::from_str(json).map_err(|e| {
+        log::error!("deserialization error: {}", e);
+        Errors::from_serde(json, &e)
     })
 }
+
+pub fn validate_document(json: &str) -> Result<Vec<validate::Problem>, Errors> {
+    validate::validate(json)
+}
+
+pub fn input_schema() -> String {
+    serde_json::to_string_pretty(&Input::json_schema()).unwrap()
+}
+
+pub fn has_terminal() -> bool {
+    terminal::open_tty().is_ok()
+}
@@ -6,6 +6,14 @@ use log::{LevelFilter};
 use clap::{Arg, App};
 use atty::Stream;
 use env_logger::Builder;
+use tooey::config::{Config, InitialDepth, ListStyle, ViewportHeight};
+use tooey::session::{Session};
+use tooey::export::{ExportFormat, ExportScope};
+use tooey::dump::{DumpOptions};
+use tooey::keymap::{Key};
+use tooey::options::{RenderOptions};
+use tooey::headless::{HeadlessRun, describe_event};
+use tooey::recording::{Recording};
 
 fn get_json_from_file(file_name: &str) -> String {
     let mut file = File::open(file_name).unwrap_or_else(|err| {
@@ -32,6 +40,41 @@ fn load_stdin() -> io::Result<String> {
     return Ok(buffer);
 }
 
+fn print_session(session: &Session, with_ids: bool) {
+    if session.values.is_empty() {
+        if let Some(value) = &session.value {
+            println!("{}", value);
+        }
+        return;
+    }
+
+    for selected in &session.values {
+        if with_ids {
+            println!("{}\t{}", selected.id, selected.value);
+        } else {
+            println!("{}", selected.value);
+        }
+    }
+}
+
+fn print_frames(run: &HeadlessRun) {
+    for (step, frame) in run.frames.iter().enumerate() {
+        match &frame.event {
+            Some(event) => println!("--- {} {} ---", step, describe_event(event)),
+            None => println!("--- {} ---", step),
+        }
+        println!("{}", frame.text);
+    }
+}
+
+fn parse_size(size: &str) -> Option<(u16, u16)> {
+    let (width, height) = size.split_once('x')?;
+    let width = width.trim().parse::<u16>().ok().filter(|width| *width > 0)?;
+    let height = height.trim().parse::<u16>().ok().filter(|height| *height > 0)?;
+
+    Some((width, height))
+}
+
 fn init_logging() -> Builder {
     let mut builder = Builder::from_default_env();
 
@@ -66,8 +109,202 @@ fn main() -> io::Result<()> {
              .long("file")
              .value_name("FILE")
              .help("Provide processed document as file"))
+        .arg(Arg::with_name("depth")
+             .short('d')
+             .long("depth")
+             .value_name("DEPTH")
+             .help("Initial depth, either a number or \"auto\" to pick one from the document structure"))
+        .arg(Arg::with_name("list-style")
+             .long("list-style")
+             .value_name("STYLE")
+             .possible_values(&["bullet", "numbered"])
+             .help("Marker style for lists"))
+        .arg(Arg::with_name("height")
+             .long("height")
+             .value_name("HEIGHT")
+             .help("Draw below the cursor in this many lines, or a percentage of the terminal such as 40%, instead of the full screen"))
+        .arg(Arg::with_name("clipboard-command")
+             .long("clipboard-command")
+             .value_name("COMMAND")
+             .help("Command that receives yanked text on stdin when OSC 52 cannot be used"))
+        .arg(Arg::with_name("opener")
+             .long("opener")
+             .value_name("COMMAND")
+             .help("Command used to open links, {} is replaced with the URL"))
+        .arg(Arg::with_name("export")
+             .long("export")
+             .value_name("FORMAT")
+             .possible_values(&["text", "md", "markdown", "html"])
+             .help("Write the document in the given format instead of starting the interface"))
+        .arg(Arg::with_name("export-scope")
+             .long("export-scope")
+             .value_name("SCOPE")
+             .possible_values(&["document", "depth"])
+             .default_value("document")
+             .help("Export the whole document or only the items at the initial depth"))
+        .arg(Arg::with_name("output")
+             .short('o')
+             .long("output")
+             .value_name("FILE")
+             .help("File to write the export to, defaults to stdout"))
+        .arg(Arg::with_name("dump")
+             .long("dump")
+             .help("Print the rendered document instead of starting the interface. This is the default only when /dev/tty can't be opened; with stdout redirected, as in $(tooey) or tooey | xargs, the interface still runs on the terminal and only the selected value goes to stdout"))
+        .arg(Arg::with_name("color")
+             .long("color")
+             .value_name("WHEN")
+             .possible_values(&["auto", "always", "never"])
+             .default_value("auto")
+             .help("Colour dumped output with ANSI escapes"))
+        .arg(Arg::with_name("width")
+             .long("width")
+             .value_name("COLUMNS")
+             .help("Wrap dumped output at this width"))
+        .arg(Arg::with_name("numbers")
+             .long("numbers")
+             .help("Number the items in dumped output"))
+        .arg(Arg::with_name("with-ids")
+             .long("with-ids")
+             .help("Prefix each returned value with the id of its item"))
+        .arg(Arg::with_name("keys")
+             .long("keys")
+             .value_name("KEYS")
+             .help("Run without a terminal, pressing these keys in order, such as jj<Enter>"))
+        .arg(Arg::with_name("size")
+             .long("size")
+             .value_name("COLUMNSxROWS")
+             .default_value("80x24")
+             .help("Screen size for --keys"))
+        .arg(Arg::with_name("frames")
+             .long("frames")
+             .help("Print the screen after each key given with --keys"))
+        .arg(Arg::with_name("record")
+             .long("record")
+             .value_name("FILE")
+             .conflicts_with_all(&["keys", "dump", "export"])
+             .help("Save the document, settings and every key press to FILE for replay"))
+        .subcommand(App::new("validate")
+             .about("Report every structural problem in a document")
+             .arg(Arg::with_name("FILE")
+                  .help("Document to check, read from stdin when omitted")))
+        .subcommand(App::new("schema")
+             .about("Print the JSON Schema for documents"))
+        .subcommand(App::new("replay")
+             .about("Play back a session saved with --record")
+             .arg(Arg::with_name("FILE")
+                  .required(true)
+                  .help("Recording to play back"))
+             .arg(Arg::with_name("headless")
+                  .long("headless")
+                  .help("Print the screen after each event instead of using the terminal"))
+             .arg(Arg::with_name("with-ids")
+                  .long("with-ids")
+                  .help("Prefix each returned value with the id of its item")))
         .get_matches();
 
+    if matches.subcommand_matches("schema").is_some() {
+        println!("{}", tooey::input_schema());
+        return Ok(());
+    }
+
+    if let Some(validate_matches) = matches.subcommand_matches("validate") {
+        if let Some(file_name) = validate_matches.value_of("FILE") {
+            json_string = get_json_from_file(file_name);
+        }
+
+        match tooey::validate_document(&json_string) {
+            Ok(problems) if problems.is_empty() => {
+                println!("ok");
+            }
+            Ok(problems) => {
+                for problem in &problems {
+                    println!("{}", problem);
+                }
+                eprintln!("{} problem(s) found", problems.len());
+                process::exit(1);
+            }
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(1);
+            }
+        }
+
+        return Ok(());
+    }
+
+    if let Some(replay_matches) = matches.subcommand_matches("replay") {
+        let path = replay_matches.value_of("FILE").unwrap();
+
+        let recording = Recording::load(std::path::Path::new(path)).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(1);
+        });
+
+        let options = RenderOptions::from_config(recording.config.clone())
+            .keymap(recording.keymap.clone());
+
+        let result = if replay_matches.is_present("headless") {
+            tooey::render_headless_events(
+                &recording.document,
+                options,
+                recording.width,
+                recording.height,
+                &recording.to_events()
+            ).map(|run| {
+                print_frames(&run);
+                run.session
+            })
+        } else {
+            tooey::render_input(&recording.document, options.replay(recording.events.clone()))
+        };
+
+        match result {
+            Ok(session) => {
+                let with_ids = replay_matches.is_present("with-ids") || matches.is_present("with-ids");
+                print_session(&session, with_ids);
+            }
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(1);
+            }
+        }
+
+        return Ok(());
+    }
+
+    let mut config = Config::load().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    });
+
+    if let Some(depth) = matches.value_of("depth") {
+        log::debug!("depth: {}", depth);
+
+        config.initial_depth = InitialDepth::parse(depth).unwrap_or_else(|| {
+            eprintln!("Invalid depth: {}", depth);
+            process::exit(1);
+        });
+    }
+
+    if let Some(list_style) = matches.value_of("list-style") {
+        config.list_style = ListStyle::parse(list_style).unwrap();
+    }
+
+    if let Some(height) = matches.value_of("height") {
+        config.height = Some(ViewportHeight::parse(height).unwrap_or_else(|| {
+            eprintln!("Invalid height: {}", height);
+            process::exit(1);
+        }));
+    }
+
+    if let Some(clipboard_command) = matches.value_of("clipboard-command") {
+        config.clipboard_command = Some(clipboard_command.to_string());
+    }
+
+    if let Some(opener) = matches.value_of("opener") {
+        config.opener = Some(opener.to_string());
+    }
+
     if let Some(file_name) = matches.value_of("file") {
         log::debug!("file_name: {}", file_name);
 
@@ -81,14 +318,113 @@ fn main() -> io::Result<()> {
         return Ok(());
     }
 
-    let result = tooey::render(json_string, None);
+    if let Some(format) = matches.value_of("export") {
+        let format = ExportFormat::parse(format).unwrap();
+        let scope = ExportScope::parse(matches.value_of("export-scope").unwrap()).unwrap();
+
+        match tooey::export_document(json_string, config, format, scope) {
+            Ok(exported) => {
+                match matches.value_of("output") {
+                    Some(path) => {
+                        std::fs::write(path, exported).unwrap_or_else(|err| {
+                            eprintln!("Failed to write {}: {}", path, err);
+                            process::exit(1);
+                        });
+                    }
+                    None => print!("{}", exported),
+                }
+            }
+            Err(err) => {
+                eprintln!("export failed: {}", err);
+                process::exit(1);
+            }
+        }
+
+        return Ok(());
+    }
+
+    if let Some(keys) = matches.value_of("keys") {
+        let keys = Key::parse_sequence(keys).unwrap_or_else(|err| {
+            eprintln!("Invalid keys: {}", err);
+            process::exit(1);
+        });
+
+        let (width, height) = parse_size(matches.value_of("size").unwrap()).unwrap_or_else(|| {
+            eprintln!("Invalid size: {}", matches.value_of("size").unwrap());
+            process::exit(1);
+        });
+
+        let result = tooey::parse_input(&json_string).and_then(|input| {
+            tooey::render_headless(&input, RenderOptions::from_config(config), width, height, &keys)
+        });
+
+        match result {
+            Ok(run) => {
+                if matches.is_present("frames") {
+                    print_frames(&run);
+                }
+
+                print_session(&run.session, matches.is_present("with-ids"));
+            }
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(1);
+            }
+        }
+
+        return Ok(());
+    }
+
+    // Redirected stdout alone doesn't mean dumping, it is how the selected
+    // value gets captured
+    if matches.is_present("dump") || !tooey::has_terminal() {
+        let width = match matches.value_of("width") {
+            Some(width) => width.parse::<usize>().unwrap_or_else(|_| {
+                eprintln!("Invalid width: {}", width);
+                process::exit(1);
+            }),
+            None => 160,
+        };
+
+        let color = match matches.value_of("color").unwrap() {
+            "always" => true,
+            "never" => false,
+            _ => atty::is(Stream::Stdout),
+        };
+
+        let options = DumpOptions {
+            color: color,
+            width: width,
+            numbers: matches.is_present("numbers"),
+        };
+
+        match tooey::dump_document(json_string, config, options) {
+            Ok(dumped) => print!("{}", dumped),
+            Err(err) => {
+                eprintln!("dump failed: {}", err);
+                process::exit(1);
+            }
+        }
+
+        return Ok(());
+    }
+
+    let mut options = RenderOptions::from_config(config);
+
+    if let Some(path) = matches.value_of("record") {
+        options = options.record(path);
+    }
+
+    let result = tooey::parse_input(&json_string).and_then(|input| tooey::render_input(&input, options));
 
     match result {
         Ok(session_result) => {
-            println!("{:?}", session_result);
+            log::debug!("session: {:?}", session_result);
+            print_session(&session_result, matches.is_present("with-ids"));
         }
         Err(err) => {
-            println!("session ended in error: {:?}", err);
+            eprintln!("session ended in error: {}", err);
+            process::exit(1);
         }
     }
 
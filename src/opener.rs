@@ -0,0 +1,130 @@
+use serde::{Serialize, Deserialize};
+use std::process::{Child, Command, Stdio};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OpenRule {
+    pub pattern: String,
+    pub command: String,
+}
+
+// The command for a URL: the first matching rule, then the configured opener,
+// then $BROWSER, then the platform default
+pub fn resolve_command(url: &str, opener: &Option<String>, rules: &Vec<OpenRule>) -> String {
+    if let Some(rule) = rules.iter().find(|rule| glob_match(&rule.pattern, url)) {
+        log::debug!("URL {} matched pattern {}", url, rule.pattern);
+        return rule.command.clone();
+    }
+
+    if let Some(opener) = opener {
+        return opener.clone();
+    }
+
+    if let Ok(browser) = std::env::var("BROWSER") {
+        if !browser.trim().is_empty() {
+            return browser;
+        }
+    }
+
+    if cfg!(target_os = "macos") {
+        "open".to_string()
+    } else {
+        "xdg-open".to_string()
+    }
+}
+
+pub fn spawn(url: &str, command: &str) -> Result<Child, String> {
+    log::trace!("In spawn");
+
+    let quoted = shell_quote(url);
+
+    let command_line = if command.contains("{}") {
+        command.replace("{}", &quoted)
+    } else {
+        format!("{} {}", command, quoted)
+    };
+
+    log::debug!("Opening with: {}", command_line);
+
+    Command::new("sh")
+        .arg("-c")
+        .arg(&command_line)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to run {}: {}", command_line, e))
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+// Glob matching where * matches any run of characters and everything else is literal
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let first = parts[0];
+    let last = parts[parts.len() - 1];
+
+    if !text.starts_with(first) || text.len() < first.len() + last.len() || !text.ends_with(last) {
+        return false;
+    }
+
+    let mut remaining = &text[first.len()..text.len() - last.len()];
+
+    for part in &parts[1..parts.len() - 1] {
+        match remaining.find(part) {
+            Some(index) => remaining = &remaining[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_patterns() {
+        let cases: [(&str, &str, bool); 16] = [
+            ("*", "", true),
+            ("*", "anything", true),
+            ("abc", "abc", true),
+            ("abc", "abcd", false),
+            ("a*", "a", true),
+            ("a*", "ba", false),
+            ("*a", "ba", true),
+            ("a*a", "a", false),
+            ("a*a", "aa", true),
+            ("a*a", "aba", true),
+            ("a*b*c", "axxbyyc", true),
+            ("a*b*c", "acb", false),
+            ("é*", "état", true),
+            ("*ö", "göteborg ö", true),
+            ("日*本", "日本", true),
+            ("日*本", "日", false),
+        ];
+
+        for (pattern, text, expected) in cases {
+            assert_eq!(glob_match(pattern, text), expected, "{:?} against {:?}", pattern, text);
+        }
+    }
+
+    #[test]
+    fn rules_win_over_the_opener() {
+        let rules = vec![OpenRule {
+            pattern: "https://*.youtube.com/*".to_string(),
+            command: "mpv".to_string(),
+        }];
+        let opener = Some("firefox".to_string());
+
+        assert_eq!(resolve_command("https://www.youtube.com/watch", &opener, &rules), "mpv");
+        assert_eq!(resolve_command("https://example.com/", &opener, &rules), "firefox");
+    }
+}
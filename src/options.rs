@@ -0,0 +1,137 @@
+use crate::config::{Config, ColorPalette, InitialDepth, ListStyle, ViewportHeight};
+use crate::history::{History};
+use crate::input::{Input};
+use crate::keymap::{Keymap};
+use crate::recording::{RecordedEvent};
+use std::io::{Write};
+use std::path::{PathBuf};
+
+// Called with the URL of a followed link. Returning a document shows it in
+// place of the current one instead of exiting with the URL
+pub type Loader = Box<dyn FnMut(&str) -> Result<Input, String>>;
+
+// Where copied text is sent as an OSC 52 sequence, normally the terminal the
+// app is drawn on
+pub type ClipboardOutput = Box<dyn Write>;
+
+pub struct RenderOptions {
+    pub(crate) config: Config,
+    pub(crate) keymap: Keymap,
+    pub(crate) selection: Option<String>,
+    pub(crate) history: Option<History>,
+    pub(crate) loader: Option<Loader>,
+    pub(crate) clipboard_output: Option<ClipboardOutput>,
+    pub(crate) record: Option<PathBuf>,
+    pub(crate) replay: Vec<RecordedEvent>,
+    pub(crate) side_effects: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions::from_config(Config::default())
+    }
+}
+
+impl RenderOptions {
+    pub fn new() -> RenderOptions {
+        RenderOptions::default()
+    }
+
+    pub fn from_config(config: Config) -> RenderOptions {
+        RenderOptions {
+            config: config,
+            keymap: Keymap::default(),
+            selection: None,
+            history: None,
+            loader: None,
+            clipboard_output: None,
+            record: None,
+            replay: Vec::new(),
+            side_effects: true,
+        }
+    }
+
+    pub fn theme(mut self, theme: ColorPalette) -> RenderOptions {
+        self.config.theme = theme;
+        self
+    }
+
+    pub fn keymap(mut self, keymap: Keymap) -> RenderOptions {
+        self.keymap = keymap;
+        self
+    }
+
+    pub fn initial_depth(mut self, depth: InitialDepth) -> RenderOptions {
+        self.config.initial_depth = depth;
+        self
+    }
+
+    pub fn selection(mut self, id: &str) -> RenderOptions {
+        self.selection = Some(id.to_string());
+        self
+    }
+
+    pub fn history(mut self, history: History) -> RenderOptions {
+        self.history = Some(history);
+        self
+    }
+
+    pub fn loader<F>(mut self, loader: F) -> RenderOptions
+    where
+        F: FnMut(&str) -> Result<Input, String> + 'static,
+    {
+        self.loader = Some(Box::new(loader));
+        self
+    }
+
+    // Hosts that draw somewhere other than the controlling terminal pass the
+    // writer for their own, without one copying needs a clipboard command
+    pub fn clipboard_output<W>(mut self, output: W) -> RenderOptions
+    where
+        W: Write + 'static,
+    {
+        self.clipboard_output = Some(Box::new(output));
+        self
+    }
+
+    // Saves the document, settings and every event to this file when the
+    // session ends
+    pub fn record(mut self, path: &str) -> RenderOptions {
+        self.record = Some(PathBuf::from(path));
+        self
+    }
+
+    // Plays these events back at their recorded times before handing control
+    // to the keyboard
+    pub fn replay(mut self, events: Vec<RecordedEvent>) -> RenderOptions {
+        self.replay = events;
+        self
+    }
+
+    // With side effects off, copying, opening links, running commands and
+    // exporting to a file only report what they would have done
+    pub fn side_effects(mut self, enabled: bool) -> RenderOptions {
+        self.side_effects = enabled;
+        self
+    }
+
+    pub fn list_style(mut self, list_style: ListStyle) -> RenderOptions {
+        self.config.list_style = list_style;
+        self
+    }
+
+    pub fn height(mut self, height: ViewportHeight) -> RenderOptions {
+        self.config.height = Some(height);
+        self
+    }
+
+    pub fn clipboard_command(mut self, command: &str) -> RenderOptions {
+        self.config.clipboard_command = Some(command.to_string());
+        self
+    }
+
+    pub fn opener(mut self, command: &str) -> RenderOptions {
+        self.config.opener = Some(command.to_string());
+        self
+    }
+}
@@ -0,0 +1,177 @@
+use crossterm::event::{Event, KeyEventKind};
+use serde::{Serialize, Deserialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::config::{Config};
+use crate::error::{Errors};
+use crate::input::{Input};
+use crate::keymap::{Key, Keymap};
+
+const RECORDING_VERSION: u32 = 1;
+
+// Everything needed to play a session back: the document, the settings and
+// key bindings it was shown with, the screen size and each event with its time
+// from the start
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Recording {
+    pub version: u32,
+    pub width: u16,
+    pub height: u16,
+    pub config: Config,
+    #[serde(default)]
+    pub keymap: Keymap,
+    pub document: Input,
+    pub events: Vec<RecordedEvent>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecordedEvent {
+    pub at_ms: u64,
+    #[serde(flatten)]
+    pub kind: RecordedKind,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RecordedKind {
+    Key { key: String },
+    Resize { width: u16, height: u16 },
+}
+
+// Shared so the recording can still be saved from the panic hook
+#[derive(Clone)]
+pub struct Recorder {
+    recording: Arc<Mutex<Recording>>,
+    started: Instant,
+    path: PathBuf,
+}
+
+impl RecordedEvent {
+    // Only presses and resizes affect the app, everything else is left out.
+    // So are keys the notation can't write down, such as ones held with hyper,
+    // which would otherwise play back as a different key
+    pub fn from_event(at_ms: u64, event: &Event) -> Option<RecordedEvent> {
+        let kind = match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                let key = Key::from(*key);
+                let text = key.to_string();
+
+                if Key::parse(&text) != Some(key) {
+                    log::debug!("Not recording {:?}, it can't be written down", key);
+                    return None;
+                }
+
+                RecordedKind::Key {
+                    key: text,
+                }
+            }
+            Event::Resize(width, height) => RecordedKind::Resize {
+                width: *width,
+                height: *height,
+            },
+            _ => return None,
+        };
+
+        Some(RecordedEvent {
+            at_ms: at_ms,
+            kind: kind,
+        })
+    }
+
+    pub fn to_event(&self) -> Option<Event> {
+        match &self.kind {
+            RecordedKind::Key { key } => Key::parse(key).map(|key| Event::Key(key.to_event())),
+            RecordedKind::Resize { width, height } => Some(Event::Resize(*width, *height)),
+        }
+    }
+}
+
+impl Recording {
+    pub fn load(path: &Path) -> Result<Recording, Errors> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| Errors::InvalidInput(format!("{}: {}", path.display(), e)))?;
+
+        let recording: Recording = serde_json::from_str(&contents)
+            .map_err(|e| Errors::from_serde(&contents, &e))?;
+
+        if recording.version != RECORDING_VERSION {
+            return Err(Errors::InvalidInput(format!(
+                "{}: unsupported recording version {}",
+                path.display(),
+                recording.version
+            )));
+        }
+
+        Ok(recording)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Errors> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| Errors::UnexpectedError(e.to_string()))?;
+
+        std::fs::write(path, contents)
+            .map_err(|e| Errors::UnexpectedError(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn to_events(&self) -> Vec<Event> {
+        self.events
+            .iter()
+            .filter_map(|event| event.to_event())
+            .collect()
+    }
+}
+
+impl Recorder {
+    pub fn new(
+        path: &Path,
+        document: &Input,
+        config: &Config,
+        keymap: &Keymap,
+        width: u16,
+        height: u16
+    ) -> Recorder {
+        let recording = Recording {
+            version: RECORDING_VERSION,
+            width: width,
+            height: height,
+            config: config.clone(),
+            keymap: keymap.clone(),
+            document: document.clone(),
+            events: Vec::new(),
+        };
+
+        Recorder {
+            recording: Arc::new(Mutex::new(recording)),
+            started: Instant::now(),
+            path: path.to_path_buf(),
+        }
+    }
+
+    pub fn record(&self, event: &Event) {
+        let at_ms = self.started.elapsed().as_millis() as u64;
+
+        if let Some(recorded) = RecordedEvent::from_event(at_ms, event) {
+            if let Ok(mut recording) = self.recording.lock() {
+                recording.events.push(recorded);
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Errors> {
+        // A panic while recording leaves the lock poisoned, what was recorded
+        // up to then is still worth keeping
+        let recording = match self.recording.try_lock() {
+            Ok(recording) => recording,
+            Err(std::sync::TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(std::sync::TryLockError::WouldBlock) => {
+                return Err(Errors::UnexpectedError("the recording is in use".to_string()));
+            }
+        };
+
+        log::debug!("Saving {} events to {}", recording.events.len(), self.path.display());
+
+        recording.save(&self.path)
+    }
+}
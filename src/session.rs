@@ -4,4 +4,12 @@ use serde::{Serialize, Deserialize};
 pub struct Session {
     pub depth: usize,
     pub value: Option<String>,
+    #[serde(default)]
+    pub values: Vec<SelectedValue>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SelectedValue {
+    pub id: String,
+    pub value: String,
 }
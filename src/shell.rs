@@ -0,0 +1,56 @@
+use std::io::{Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+
+pub struct CommandOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub status: String,
+    pub success: bool,
+}
+
+// Runs the command on its own thread so a slow one doesn't hold up the
+// interface, the output arrives on the returned channel once it exits
+pub fn spawn_with_input(command: &str, input: &str) -> Receiver<Result<CommandOutput, String>> {
+    let (sender, receiver) = mpsc::channel();
+    let command = command.to_string();
+    let input = input.to_string();
+
+    std::thread::spawn(move || {
+        let _ = sender.send(run_with_input(&command, &input));
+    });
+
+    receiver
+}
+
+pub fn run_with_input(command: &str, input: &str) -> Result<CommandOutput, String> {
+    log::trace!("In run_with_input");
+    log::debug!("command: {}", command);
+
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run {}: {}", command, e))?;
+
+    // Writing from another thread keeps a command that produces output before
+    // reading all of its input from blocking on a full pipe
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || {
+        let _ = stdin.write_all(input.as_bytes());
+    });
+
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    let _ = writer.join();
+
+    Ok(CommandOutput {
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        status: output.status.to_string(),
+        success: output.status.success(),
+    })
+}
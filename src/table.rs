@@ -0,0 +1,286 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::str::FromStr;
+use ratatui::prelude::*;
+
+use crate::input::{Content, ContentValue, LineOptions, RenderedLines, ValuePath};
+
+const MIN_COLUMN_WIDTH: usize = 4;
+const COLUMN_SEPARATOR: &str = "   ";
+
+#[derive(Clone, Debug)]
+pub struct TableSort {
+    pub column: usize,
+    pub descending: bool,
+}
+
+// A list renders as a table when every item is a flat record with the same
+// set of value names
+pub fn list_schema(list: &Vec<Content>) -> Option<Vec<String>> {
+    if list.len() < 2 {
+        return None;
+    }
+
+    let first = list.first()?;
+    let columns: Vec<String> = first.values
+        .iter()
+        .map(|value| value.name.clone())
+        .fold(Vec::new(), |mut columns, name| {
+            if !columns.contains(&name) {
+                columns.push(name);
+            }
+            columns
+        });
+
+    if columns.len() < 2 {
+        return None;
+    }
+
+    let expected: HashSet<&String> = columns.iter().collect();
+
+    let is_homogeneous = list.iter().all(|item| {
+        let names: HashSet<&String> = item.values.iter().map(|value| &value.name).collect();
+        names == expected && !item.has_nested_content()
+    });
+
+    if is_homogeneous {
+        Some(columns)
+    } else {
+        None
+    }
+}
+
+pub fn table_keys(content: &Content, result: &mut Vec<(String, usize)>) {
+    for (index, list) in content.lists.iter().enumerate() {
+        if let Some(columns) = list_schema(list) {
+            result.push((content.list_key(index), columns.len()));
+        }
+
+        for item in list {
+            table_keys(item, result);
+        }
+    }
+
+    for child in content.inner_content.iter().chain(content.children.iter()) {
+        table_keys(child, result);
+    }
+}
+
+pub fn table_to_lines(
+    list: &Vec<Content>,
+    columns: &Vec<String>,
+    sort: Option<&TableSort>,
+    options: &LineOptions,
+    result: &mut RenderedLines,
+    indent_size: usize,
+) {
+    let indent = " ".repeat(indent_size * 2);
+
+    // Columns hidden by the active filter are dropped, keeping the sort column
+    // pointing at the original index
+    let visible: Vec<usize> = (0..columns.len())
+        .filter(|index| {
+            list[0].values
+                .iter()
+                .find(|value| value.name == columns[*index])
+                .map(|value| options.filter.includes(value))
+                .unwrap_or(false)
+        })
+        .collect();
+
+    if visible.is_empty() {
+        return;
+    }
+
+    let mut rows: Vec<(&Content, Vec<Option<&ContentValue>>)> = list
+        .iter()
+        .map(|item| {
+            let cells = columns
+                .iter()
+                .map(|column| item.values.iter().find(|value| &value.name == column))
+                .collect();
+
+            (item, cells)
+        })
+        .collect();
+
+    if let Some(sort) = sort {
+        rows.sort_by(|(_, a), (_, b)| {
+            let ordering = compare_cells(a[sort.column], b[sort.column]);
+
+            if sort.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    let columns: Vec<String> = visible.iter().map(|index| columns[*index].clone()).collect();
+    let (items, rows): (Vec<&Content>, Vec<Vec<Option<&ContentValue>>>) = rows
+        .into_iter()
+        .map(|(item, row)| (item, visible.iter().map(|index| row[*index]).collect()))
+        .unzip();
+    let sort_column = sort.and_then(|sort| visible.iter().position(|index| *index == sort.column));
+
+    let widths = fit_column_widths(&columns, &rows, options.width.saturating_sub(indent.len()));
+
+    let base_style = Style::new().fg(options.text_color).bg(options.background_color);
+
+    let mut header: Vec<Span<'static>> = vec![Span::raw(indent.clone())];
+    for (index, column) in columns.iter().enumerate() {
+        let indicator = match sort {
+            Some(sort) if sort_column == Some(index) && sort.descending => " ▼",
+            Some(_) if sort_column == Some(index) => " ▲",
+            _ => "",
+        };
+
+        let title = truncate(&format!("{}{}", column, indicator), widths[index]);
+        let padding = widths[index] - title.chars().count();
+
+        if index > 0 {
+            header.push(Span::styled(COLUMN_SEPARATOR, base_style));
+        }
+        header.push(Span::styled(title, base_style.add_modifier(Modifier::BOLD)));
+        header.push(Span::raw(" ".repeat(padding)));
+    }
+    result.push(Line::from(header), Vec::new());
+
+    for (item, row) in items.iter().zip(rows.iter()) {
+        let mut spans: Vec<Span<'static>> = vec![Span::raw(indent.clone())];
+        let mut values: Vec<(ValuePath, ContentValue)> = Vec::new();
+
+        for (index, cell) in row.iter().enumerate() {
+            let text = cell
+                .map(|value| value.value.split_whitespace().collect::<Vec<&str>>().join(" "))
+                .unwrap_or_default();
+            let text = truncate(&text, widths[index]);
+            let padding = widths[index] - text.chars().count();
+
+            let mut style = cell.map(|value| cell_style(value, options)).unwrap_or(base_style);
+
+            if let Some(value) = cell {
+                // Cells borrow from the item's values, so its position there is its path
+                let index = item.values.iter().position(|candidate| std::ptr::eq(candidate, *value)).unwrap_or(0);
+                let path = item.value_path(index);
+
+                let ordinal = result.value_count() + values.len();
+                if options.highlighted_value == Some(ordinal) {
+                    style = style.bg(options.highlight_color);
+                } else if options.marked_values.contains(&path) {
+                    style = style.bg(options.mark_color);
+                }
+                values.push((path, (*value).clone()));
+            }
+
+            if index > 0 {
+                spans.push(Span::styled(COLUMN_SEPARATOR, base_style));
+            }
+            spans.push(Span::styled(text, style));
+            spans.push(Span::raw(" ".repeat(padding)));
+        }
+
+        result.push(Line::from(spans), values);
+    }
+}
+
+fn cell_style(value: &ContentValue, options: &LineOptions) -> Style {
+    let fg = if value.meta.is_url {
+        Color::from_str("#0000FF").unwrap()
+    } else if value.meta.is_primary_content {
+        options.main_content_color
+    } else {
+        options.text_color
+    };
+
+    let mut style = Style::new().fg(fg).bg(options.background_color);
+
+    if value.meta.is_url {
+        style = style.add_modifier(Modifier::UNDERLINED);
+    }
+
+    if value.meta.is_title {
+        style = style.add_modifier(Modifier::BOLD);
+    }
+
+    style
+}
+
+fn fit_column_widths(
+    columns: &Vec<String>,
+    rows: &Vec<Vec<Option<&ContentValue>>>,
+    available: usize,
+) -> Vec<usize> {
+    let mut widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            rows
+                .iter()
+                .filter_map(|row| row[index])
+                .map(|value| value.value.split_whitespace().collect::<Vec<&str>>().join(" ").chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(column.chars().count() + 2)
+        })
+        .collect();
+
+    let available = available.saturating_sub(COLUMN_SEPARATOR.chars().count() * (columns.len() - 1));
+
+    // Shrink the widest column one character at a time until the table fits
+    while widths.iter().sum::<usize>() > available {
+        let (widest, width) = widths
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, width)| **width)
+            .map(|(index, width)| (index, *width))
+            .unwrap();
+
+        if width <= MIN_COLUMN_WIDTH {
+            break;
+        }
+
+        widths[widest] -= 1;
+    }
+
+    widths
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+
+    let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+fn compare_cells(a: Option<&ContentValue>, b: Option<&ContentValue>) -> Ordering {
+    let a = a.map(|value| value.value.trim()).unwrap_or("");
+    let b = b.map(|value| value.value.trim()).unwrap_or("");
+
+    // Cells starting with a number come before text, so the order stays
+    // consistent when a column mixes both
+    let key = |text: &str| {
+        let number = leading_number(text);
+        (number.is_none(), number.unwrap_or(0.0), text.to_lowercase())
+    };
+
+    let (a, b) = (key(a), key(b));
+
+    a.0.cmp(&b.0)
+        .then_with(|| a.1.total_cmp(&b.1))
+        .then_with(|| a.2.cmp(&b.2))
+}
+
+fn leading_number(text: &str) -> Option<f64> {
+    let number: String = text
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit() && *c != '-')
+        .take_while(|c| c.is_ascii_digit() || *c == '.' || *c == '-' || *c == ',')
+        .filter(|c| *c != ',')
+        .collect();
+
+    number.parse::<f64>().ok()
+}
@@ -1,5 +1,6 @@
 use crossterm::{
-    event::{self, Event, KeyCode::Char, KeyCode},
+    cursor::{MoveTo, Show},
+    event::{self, Event, KeyCode::Char, KeyModifiers},
     execute,
     style::{Color, SetBackgroundColor},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, Clear, ClearType},
@@ -10,103 +11,489 @@ use ratatui::{widgets::ListItem as RListItem};
 use textwrap;
 use pandoculation;
 use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Write};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use std::os::unix::io::{AsRawFd};
+use std::sync::atomic::{AtomicBool, Ordering};
+use libc::{c_int, SIGHUP, SIGINT, SIGTERM, SIGTSTP};
 
 use crate::input::*;
 use crate::session::*;
 use crate::app::{App};
-use crate::history::{History};
+use crate::config::{ViewportHeight};
+use crate::options::{RenderOptions};
+use crate::recording::{Recorder, RecordedEvent};
 
 type Err = Box<dyn std::error::Error>;
 type Result<T> = std::result::Result<T, Err>;
 
-pub fn start_interface(input: &Input, history: &Option<History>) -> Result<Session> {
+enum Update {
+    Continue,
+    Suspend,
+}
+
+struct Replay {
+    events: VecDeque<RecordedEvent>,
+    started: Instant,
+}
+
+// How long a terminal gets to answer a cursor position request
+const CURSOR_REPORT_TIMEOUT: Duration = Duration::from_millis(200);
+
+// Spelled out through PanicInfo, which still works on compilers that predate
+// PanicHookInfo
+#[allow(deprecated)]
+type PanicHook = Box<dyn Fn(&std::panic::PanicInfo<'_>) + Sync + Send + 'static>;
+
+static TERMINATE: AtomicBool = AtomicBool::new(false);
+static SUSPEND: AtomicBool = AtomicBool::new(false);
+
+// The handlers that were in place before the interface started, put back when
+// it is dropped so a host program keeps its own signal handling
+struct Signals {
+    previous: Vec<(c_int, libc::sigaction)>,
+}
+
+// The panic hook that was in place before the interface started
+struct PanicGuard {
+    previous: Option<PanicHook>,
+}
+
+// All drawing and key input goes through the controlling terminal, leaving
+// stdin for the document and stdout for the selected value
+pub fn open_tty() -> std::io::Result<File> {
+    OpenOptions::new().read(true).write(true).open("/dev/tty")
+}
+
+pub fn start_interface(input: &Input, options: RenderOptions) -> Result<Session> {
     log::trace!("In start_interface");
 
-    startup()?;
+    let mut tty = open_tty().map_err(|e| {
+        log::error!("Failed to open /dev/tty: {}", e);
+        e
+    })?;
+
+    // Whatever can fail is done before the terminal changes mode, past that
+    // point every exit goes through shutdown
+    let run_tty = tty.try_clone()?;
+    let terminal_size = crossterm::terminal::size()?;
+    let signals = Signals::register()?;
+
+    let mut inline_area = startup(&mut tty, &options.config.height)?;
+
+    let recorder = match &options.record {
+        Some(path) => {
+            let (width, height) = match inline_area {
+                Some(area) => (area.width, area.height),
+                None => terminal_size,
+            };
+
+            Some(Recorder::new(path, input, &options.config, &options.keymap, width, height))
+        }
+        None => None,
+    };
+
+    let _panic_guard = install_panic_hook(inline_area, recorder.clone());
+
+    let result = run(run_tty, &mut inline_area, &signals, recorder.as_ref(), input, options);
 
-    let result = run(input, history);
+    let shutdown_result = shutdown(&mut tty, inline_area);
+
+    // Saved however the session ended, a recording of a failure is the one
+    // most worth having
+    if let Some(recorder) = &recorder {
+        match (&result, recorder.save()) {
+            (_, Ok(())) => {}
+            (Ok(_), Err(e)) => return Err(Box::new(e)),
+            (Err(_), Err(e)) => log::error!("Failed to save the recording: {}", e),
+        }
+    }
 
-    shutdown()?;
+    shutdown_result?;
 
     result
 }
 
-fn startup() -> Result<()> {
+// Puts the terminal back before the panic message is printed, otherwise it
+// is lost on the alternate screen and the shell is left in raw mode
+fn install_panic_hook(inline_area: Option<Rect>, recorder: Option<Recorder>) -> PanicGuard {
+    let (panic_hook, _) = color_eyre::config::HookBuilder::default().into_hooks();
+
+    let previous = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |panic_info| {
+        if let Ok(mut tty) = open_tty() {
+            let _ = shutdown(&mut tty, inline_area);
+        }
+
+        if let Some(recorder) = &recorder {
+            if let Err(e) = recorder.save() {
+                eprintln!("Failed to save the recording: {}", e);
+            }
+        }
+
+        eprintln!("{}", panic_hook.panic_report(panic_info));
+    }));
+
+    PanicGuard {
+        previous: Some(previous),
+    }
+}
+
+impl Drop for PanicGuard {
+    fn drop(&mut self) {
+        // The hook can't be changed while unwinding, ours stays in place then
+        if std::thread::panicking() {
+            return;
+        }
+
+        if let Some(previous) = self.previous.take() {
+            std::panic::set_hook(previous);
+        }
+    }
+}
+
+// The handlers only raise flags, the main loop then quits or suspends so the
+// terminal is always restored by the usual shutdown
+extern "C" fn on_signal(signal: c_int) {
+    match signal {
+        SIGTSTP => SUSPEND.store(true, Ordering::Relaxed),
+        _ => TERMINATE.store(true, Ordering::Relaxed),
+    }
+}
+
+fn set_signal_handler(signal: c_int, handler: libc::sighandler_t) -> std::io::Result<libc::sigaction> {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+
+        let mut previous: libc::sigaction = std::mem::zeroed();
+
+        if libc::sigaction(signal, &action, &mut previous) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        Ok(previous)
+    }
+}
+
+impl Signals {
+    fn register() -> Result<Signals> {
+        TERMINATE.store(false, Ordering::Relaxed);
+        SUSPEND.store(false, Ordering::Relaxed);
+
+        let mut signals = Signals {
+            previous: Vec::new(),
+        };
+
+        for signal in [SIGINT, SIGTERM, SIGHUP, SIGTSTP] {
+            // Anything registered so far is put back when signals is dropped
+            let previous = set_signal_handler(signal, on_signal as extern "C" fn(c_int) as libc::sighandler_t)?;
+            signals.previous.push((signal, previous));
+        }
+
+        Ok(signals)
+    }
+
+    fn terminate_requested(&self) -> bool {
+        TERMINATE.load(Ordering::Relaxed)
+    }
+
+    fn request_suspend(&self) {
+        SUSPEND.store(true, Ordering::Relaxed);
+    }
+
+    fn take_suspend(&self) -> bool {
+        SUSPEND.swap(false, Ordering::Relaxed)
+    }
+
+    // Stops the process the way the default SIGTSTP handler would, returning
+    // once it is resumed
+    fn stop(&self) -> std::io::Result<()> {
+        set_signal_handler(SIGTSTP, libc::SIG_DFL)?;
+
+        unsafe {
+            libc::raise(SIGTSTP);
+        }
+
+        set_signal_handler(SIGTSTP, on_signal as extern "C" fn(c_int) as libc::sighandler_t)?;
+        Ok(())
+    }
+}
+
+impl Drop for Signals {
+    fn drop(&mut self) {
+        for (signal, previous) in self.previous.iter() {
+            unsafe {
+                libc::sigaction(*signal, previous, std::ptr::null_mut());
+            }
+        }
+    }
+}
+
+fn startup(tty: &mut File, height: &Option<ViewportHeight>) -> Result<Option<Rect>> {
     enable_raw_mode().map_err(|e| {
         log::error!("Failed to enable raw mode: {}", e);
         e
     })?;
-    execute!(std::io::stderr(), EnterAlternateScreen).map_err(|e| {
-        log::error!("Failed to enter alternate screen: {}", e);
-        e
-    })?;
-    Ok(())
+
+    let result = match height {
+        Some(height) => reserve_inline_area(tty, height).map(Some),
+        None => execute!(tty, EnterAlternateScreen)
+            .map(|_| None)
+            .map_err(|e| {
+                log::error!("Failed to enter alternate screen: {}", e);
+                e.into()
+            }),
+    };
+
+    // Raw mode is already on, it can't outlive a failed start
+    if result.is_err() {
+        let _ = disable_raw_mode();
+    }
+
+    result
 }
 
-fn shutdown() -> Result<()> {
-    execute!(std::io::stdout(), SetBackgroundColor(Color::Reset));
-    execute!(std::io::stderr(), LeaveAlternateScreen)?;
+fn shutdown(tty: &mut File, inline_area: Option<Rect>) -> Result<()> {
+    execute!(tty, SetBackgroundColor(Color::Reset));
+
+    match inline_area {
+        Some(area) => {
+            // Leave the scrollback as it was and put the cursor where the region started
+            for row in area.top()..area.bottom() {
+                execute!(tty, MoveTo(0, row), Clear(ClearType::CurrentLine))?;
+            }
+            execute!(tty, MoveTo(0, area.top()))?;
+        }
+        None => {
+            execute!(tty, LeaveAlternateScreen)?;
+        }
+    }
+
+    execute!(tty, Show)?;
     disable_raw_mode()?;
     Ok(())
 }
 
-fn run(input: &Input, history: &Option<History>) -> Result<Session> {
-    let mut t = Terminal::new(CrosstermBackend::new(std::io::stderr()))?;
+// Restores the terminal, stops the process as the shell expects and sets the
+// terminal up again once resumed with fg
+fn suspend(
+    tty: &mut File,
+    signals: &Signals,
+    inline_area: Option<Rect>,
+    height: &Option<ViewportHeight>
+) -> Result<Option<Rect>> {
+    log::trace!("In suspend");
+
+    shutdown(tty, inline_area)?;
+
+    signals.stop()?;
+
+    startup(tty, height)
+}
+
+fn new_terminal(tty: File, inline_area: Option<Rect>) -> Result<Terminal<CrosstermBackend<File>>> {
+    let backend = CrosstermBackend::new(tty);
+
+    let t = match inline_area {
+        Some(area) => Terminal::with_options(backend, TerminalOptions { viewport: Viewport::Fixed(area) })?,
+        None => Terminal::new(backend)?,
+    };
+
+    Ok(t)
+}
+
+fn clear(t: &mut Terminal<CrosstermBackend<File>>, inline_area: Option<Rect>, background: Color) -> Result<()> {
+    execute!(t.backend_mut(), SetBackgroundColor(background))?;
+
+    match inline_area {
+        Some(_) => t.clear()?,
+        None => {
+            execute!(t.backend_mut(), Clear(ClearType::All))?;
+        }
+    }
+
+    Ok(())
+}
+
+// Makes room below the cursor, scrolling the screen up when the cursor is too
+// close to the bottom, and returns the region to draw in
+fn reserve_inline_area(tty: &mut File, height: &ViewportHeight) -> Result<Rect> {
+    let (columns, rows) = crossterm::terminal::size()?;
+    let height = height.lines(rows);
+    let row = cursor_row(tty, rows)?.min(rows.saturating_sub(1));
+
+    tty.write_all("\n".repeat(height as usize - 1).as_bytes())?;
+    tty.flush()?;
+
+    let bottom = (row + height - 1).min(rows - 1);
+
+    Ok(Rect::new(0, bottom + 1 - height, columns, height))
+}
+
+// Asks the terminal for the cursor position directly, as crossterm would send
+// the request to stdout
+fn cursor_row(tty: &mut File, rows: u16) -> Result<u16> {
+    tty.write_all(b"\x1b[6n")?;
+    tty.flush()?;
+
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    let deadline = Instant::now() + CURSOR_REPORT_TIMEOUT;
 
-    let mut app = App::new();
-    app.load_input(input);
-    app.load_history(history);
+    loop {
+        // Some terminals never answer, the region then goes at the bottom
+        let remaining = deadline.saturating_duration_since(Instant::now());
+
+        match wait_readable(tty, remaining) {
+            Ok(true) => {}
+            Ok(false) => {
+                log::debug!("No cursor position report, using the bottom row");
+                return Ok(rows.saturating_sub(1));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(Box::new(e)),
+        }
+
+        if std::io::Read::read(tty, &mut byte)? != 1 || byte[0] == b'R' {
+            break;
+        }
+        response.push(byte[0]);
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let row = response
+        .rsplit('[')
+        .next()
+        .and_then(|position| position.split(';').next())
+        .and_then(|row| row.parse::<u16>().ok())
+        .ok_or_else(|| format!("Unexpected cursor position report: {:?}", response))?;
+
+    Ok(row.saturating_sub(1))
+}
+
+fn wait_readable(tty: &File, timeout: Duration) -> std::io::Result<bool> {
+    let mut fd = libc::pollfd {
+        fd: tty.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+
+    let ready = unsafe { libc::poll(&mut fd, 1, timeout.as_millis() as c_int) };
+
+    if ready < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(ready > 0)
+}
+
+fn run(
+    tty: File,
+    inline_area: &mut Option<Rect>,
+    signals: &Signals,
+    recorder: Option<&Recorder>,
+    input: &Input,
+    mut options: RenderOptions
+) -> Result<Session> {
+    let mut t = new_terminal(tty.try_clone()?, *inline_area)?;
+
+    if options.clipboard_output.is_none() {
+        options.clipboard_output = Some(Box::new(tty.try_clone()?));
+    }
+
+    let height = options.config.height.clone();
+
+    let mut replay = Replay {
+        events: options.replay.iter().cloned().collect(),
+        started: Instant::now(),
+    };
+
+    let mut app = App::with_input(input, options);
 
     let background_hex = app.color_palette.background_hex.clone();
     let color: Color = parse_hex_color(&background_hex).expect("Could not parse hex colour code");
 
-    execute!(
-        std::io::stdout(),
-        SetBackgroundColor(color),
-        Clear(ClearType::All)
-    );
+    clear(&mut t, *inline_area, color)?;
 
     loop {
-        t.draw(|f| {
-            f.render_widget(&mut app, f.size());
-        });
+        app.draw(&mut t)?;
 
-        update(&mut app)?;
+        let update = update(&mut app, recorder, &mut replay)?;
+        app.poll_launched();
+
+        if signals.terminate_requested() {
+            log::debug!("Terminated by signal");
+            app.exit_without_value();
+        }
 
         if app.should_quit {
             break;
         }
+
+        if let Update::Suspend = update {
+            signals.request_suspend();
+        }
+
+        if signals.take_suspend() {
+            let mut tty = tty.try_clone()?;
+            *inline_area = suspend(&mut tty, signals, *inline_area, &height)?;
+
+            t = new_terminal(tty, *inline_area)?;
+            clear(&mut t, *inline_area, color)?;
+        }
     }
 
     Ok(app.get_session())
 }
 
-fn update(app: &mut App) -> Result<()> {
+fn next_replayed_event(replay: &mut Replay) -> Option<Event> {
+    let due = replay.events
+        .front()
+        .map(|event| replay.started.elapsed().as_millis() as u64 >= event.at_ms)
+        .unwrap_or(false);
+
+    if !due {
+        return None;
+    }
+
+    replay.events.pop_front().and_then(|event| event.to_event())
+}
+
+fn update(app: &mut App, recorder: Option<&Recorder>, replay: &mut Replay) -> Result<Update> {
+    if let Some(event) = next_replayed_event(replay) {
+        log::trace!("Replayed event: {:?}", event);
+        app.handle_event(event);
+        return Ok(Update::Continue);
+    }
+
     match event::poll(std::time::Duration::from_millis(50)) {
         Ok(true) => {
             match event::read() {
                 Ok(event) => {
                     log::trace!("Event read: {:?}", event);
 
+                    // Suspending needs the terminal, the rest is up to the app
                     if let Event::Key(key) = event {
-                        if key.kind == event::KeyEventKind::Press {
-                            match key.code {
-                                Char('q') => app.exit_without_value(),
-                                Char('g') => app.display_items.start(),
-                                Char('G') => app.display_items.end(),
-                                Char('j') => app.display_items.next(),
-                                Char('k') => app.display_items.previous(),
-                                Char('h') => app.previous_value(),
-                                Char('l') => app.next_value(),
-                                Char('p') => app.toggle_primary_content(),
-                                Char('-') => app.higher(),
-                                Char('+') => app.deeper(),
-                                KeyCode::Enter => app.exit_with_value(),
-                                KeyCode::Backspace => app.try_navigate_back(),
-                                _ => {},
-                            }
+                        if key.kind == event::KeyEventKind::Press
+                            && key.code == Char('z')
+                            && key.modifiers.contains(KeyModifiers::CONTROL) {
+                            return Ok(Update::Suspend);
                         }
                     }
+
+                    if let Some(recorder) = recorder {
+                        recorder.record(&event);
+                    }
+
+                    if let Some(outcome) = app.handle_event(event) {
+                        log::debug!("outcome: {:?}", outcome);
+                    }
                 }
                 Err(e) => {
                     log::error!("Failed to read event: {}", e);
@@ -123,7 +510,7 @@ fn update(app: &mut App) -> Result<()> {
         }
     }
 
-    Ok(())
+    Ok(Update::Continue)
 }
 
 fn parse_hex_color(hex_color_str: &str) -> Result<Color> {
@@ -0,0 +1,215 @@
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::error::{Errors};
+use crate::input::{INPUT_FIELDS, CONTENT_FIELDS, CONTENT_VALUE_FIELDS, METADATA_FIELDS};
+
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+struct Validator {
+    problems: Vec<Problem>,
+    ids: HashMap<String, String>,
+    value_count: usize,
+}
+
+// Checks a document against the shape tooey expects and reports every problem
+// found, rather than stopping at the first one like deserialization does
+pub fn validate(json: &str) -> Result<Vec<Problem>, Errors> {
+    log::trace!("In validate");
+
+    if json.trim().is_empty() {
+        return Err(Errors::InvalidInput("the document is empty".to_string()));
+    }
+
+    let document: Value = serde_json::from_str(json).map_err(|e| Errors::from_serde(json, &e))?;
+
+    let mut validator = Validator {
+        problems: Vec::new(),
+        ids: HashMap::new(),
+        value_count: 0,
+    };
+
+    if let Some(fields) = validator.expect_object(&document, "$") {
+        validator.check_fields(fields, &INPUT_FIELDS, &INPUT_FIELDS, "$");
+
+        for field in INPUT_FIELDS {
+            if let Some(content) = fields.get(field) {
+                validator.check_content(content, &format!("$.{}", field));
+            }
+        }
+
+        if validator.value_count == 0 {
+            validator.report("$", "the document has no values");
+        }
+    }
+
+    Ok(validator.problems)
+}
+
+impl Validator {
+    fn report(&mut self, path: &str, message: &str) {
+        self.problems.push(Problem {
+            path: path.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    fn expect_object<'a>(&mut self, value: &'a Value, path: &str) -> Option<&'a Map<String, Value>> {
+        match value {
+            Value::Object(fields) => Some(fields),
+            other => {
+                self.report(path, &format!("expected an object, found {}", type_name(other)));
+                None
+            }
+        }
+    }
+
+    fn expect_array<'a>(&mut self, value: &'a Value, path: &str) -> Option<&'a Vec<Value>> {
+        match value {
+            Value::Array(items) => Some(items),
+            other => {
+                self.report(path, &format!("expected an array, found {}", type_name(other)));
+                None
+            }
+        }
+    }
+
+    fn check_fields(&mut self, fields: &Map<String, Value>, required: &[&str], known: &[&str], path: &str) {
+        for field in required {
+            if !fields.contains_key(*field) {
+                self.report(path, &format!("missing field `{}`", field));
+            }
+        }
+
+        for field in fields.keys() {
+            if !known.contains(&field.as_str()) {
+                self.report(&format!("{}.{}", path, field), &format!("unknown field `{}`", field));
+            }
+        }
+    }
+
+    fn check_string(&mut self, fields: &Map<String, Value>, field: &str, path: &str) {
+        if let Some(value) = fields.get(field) {
+            if !value.is_string() {
+                self.report(
+                    &format!("{}.{}", path, field),
+                    &format!("expected a string, found {}", type_name(value))
+                );
+            }
+        }
+    }
+
+    fn check_content(&mut self, content: &Value, path: &str) {
+        let fields = match self.expect_object(content, path) {
+            Some(fields) => fields,
+            None => return,
+        };
+
+        self.check_fields(fields, &["id"], &CONTENT_FIELDS, path);
+        self.check_string(fields, "id", path);
+
+        if let Some(Value::String(id)) = fields.get("id") {
+            match self.ids.get(id) {
+                Some(first_path) => {
+                    let message = format!("duplicate id \"{}\", first used at {}", id, first_path);
+                    self.report(&format!("{}.id", path), &message);
+                }
+                None => {
+                    self.ids.insert(id.clone(), path.to_string());
+                }
+            }
+        }
+
+        if let Some(values) = fields.get("values") {
+            let values_path = format!("{}.values", path);
+
+            if let Some(values) = self.expect_array(values, &values_path) {
+                for (index, value) in values.iter().enumerate() {
+                    self.check_content_value(value, &format!("{}[{}]", values_path, index));
+                }
+            }
+        }
+
+        for field in ["inner_content", "children"] {
+            if let Some(nested) = fields.get(field) {
+                let nested_path = format!("{}.{}", path, field);
+
+                if let Some(nested) = self.expect_array(nested, &nested_path) {
+                    for (index, content) in nested.iter().enumerate() {
+                        self.check_content(content, &format!("{}[{}]", nested_path, index));
+                    }
+                }
+            }
+        }
+
+        if let Some(lists) = fields.get("lists") {
+            let lists_path = format!("{}.lists", path);
+
+            if let Some(lists) = self.expect_array(lists, &lists_path) {
+                for (list_index, list) in lists.iter().enumerate() {
+                    let list_path = format!("{}[{}]", lists_path, list_index);
+
+                    if let Some(list) = self.expect_array(list, &list_path) {
+                        for (index, content) in list.iter().enumerate() {
+                            self.check_content(content, &format!("{}[{}]", list_path, index));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_content_value(&mut self, value: &Value, path: &str) {
+        let fields = match self.expect_object(value, path) {
+            Some(fields) => fields,
+            None => return,
+        };
+
+        self.value_count += 1;
+
+        self.check_fields(fields, &CONTENT_VALUE_FIELDS, &CONTENT_VALUE_FIELDS, path);
+        self.check_string(fields, "name", path);
+        self.check_string(fields, "value", path);
+
+        if let Some(meta) = fields.get("meta") {
+            let meta_path = format!("{}.meta", path);
+
+            if let Some(meta) = self.expect_object(meta, &meta_path) {
+                self.check_fields(meta, &METADATA_FIELDS, &METADATA_FIELDS, &meta_path);
+
+                for field in METADATA_FIELDS {
+                    if let Some(flag) = meta.get(field) {
+                        if !flag.is_boolean() {
+                            self.report(
+                                &format!("{}.{}", meta_path, field),
+                                &format!("expected a boolean, found {}", type_name(flag))
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}